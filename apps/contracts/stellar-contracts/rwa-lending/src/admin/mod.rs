@@ -1,8 +1,8 @@
-use soroban_sdk::{panic_with_error, Address, Env, Map, Symbol, Vec};
+use soroban_sdk::{Address, Env, Map, Symbol, Vec, panic_with_error};
 
 use crate::common::error::Error;
 use crate::common::storage::Storage;
-use crate::common::types::{InterestRateParams, PoolState, SCALAR_7};
+use crate::common::types::{InterestRateParams, PoolState, SCALAR_7, SECONDS_PER_YEAR};
 
 /// Administrative functions for the lending pool
 pub struct Admin;
@@ -33,14 +33,18 @@ impl Admin {
 
             // User balances
             b_token_balances: Map::new(env),
+            supplier_counts: Map::new(env),
             d_token_balances: Map::new(env),
+            borrower_counts: Map::new(env),
             collateral: Map::new(env),
 
             // Interest rate parameters
             interest_rate_params: Map::new(env),
+            rate_half_life: 0,
 
             // Auctions (unified structure)
             auction_data: Map::new(env),
+            min_fill_percent: 0,
 
             // Backstop
             backstop_deposits: Map::new(env),
@@ -57,6 +61,17 @@ impl Admin {
             // Admin
             admin: admin.clone(),
             collateral_factors: Map::new(env),
+            liquidation_thresholds: Map::new(env),
+            max_cdp_debt_value_usd: 0,
+            max_liq_seize_usd: 0,
+            interest_grace_seconds: 0,
+            bootstrap_reserves: Map::new(env),
+            max_suppliers: Map::new(env),
+            max_borrowers: Map::new(env),
+            max_single_borrow: Map::new(env),
+            max_price_ages: Map::new(env),
+            stale_collateral: Map::new(env),
+            collateral_depositors: Map::new(env),
             token_contracts: Map::new(env),
         };
 
@@ -85,6 +100,14 @@ impl Admin {
         }
 
         let mut storage = Storage::get(env);
+
+        // Collateral factor cannot exceed a configured liquidation threshold
+        if let Some(threshold) = storage.liquidation_thresholds.get(rwa_token.clone())
+            && factor > threshold
+        {
+            panic_with_error!(env, Error::InvalidCollateralFactor);
+        }
+
         storage.collateral_factors.set(rwa_token.clone(), factor);
         Storage::set(env, &storage);
     }
@@ -98,12 +121,34 @@ impl Admin {
             .unwrap_or(7_500_000) // Default: 75% (7 decimals)
     }
 
+    /// Set liquidation threshold for an RWA token (7 decimals)
+    /// Must be within [collateral factor, SCALAR_7]. Example: 8_500_000 = 85%
+    pub fn set_liquidation_threshold(env: &Env, rwa_token: &Address, threshold: u32) {
+        Self::require_admin(env);
+
+        if threshold > SCALAR_7 as u32 || threshold < Self::get_collateral_factor(env, rwa_token) {
+            panic_with_error!(env, Error::InvalidCollateralFactor);
+        }
+
+        let mut storage = Storage::get(env);
+        storage
+            .liquidation_thresholds
+            .set(rwa_token.clone(), threshold);
+        Storage::set(env, &storage);
+    }
+
+    /// Get liquidation threshold for an RWA token (7 decimals)
+    /// Defaults to the collateral factor when not configured
+    pub fn get_liquidation_threshold(env: &Env, rwa_token: &Address) -> u32 {
+        let storage = Storage::get(env);
+        storage
+            .liquidation_thresholds
+            .get(rwa_token.clone())
+            .unwrap_or_else(|| Self::get_collateral_factor(env, rwa_token))
+    }
+
     /// Set interest rate parameters for an asset
-    pub fn set_interest_rate_params(
-        env: &Env,
-        asset: &Symbol,
-        params: &InterestRateParams,
-    ) {
+    pub fn set_interest_rate_params(env: &Env, asset: &Symbol, params: &InterestRateParams) {
         Self::require_admin(env);
 
         // Validate parameters (7 decimals)
@@ -118,10 +163,250 @@ impl Admin {
         }
 
         let mut storage = Storage::get(env);
-        storage.interest_rate_params.set(asset.clone(), params.clone());
+        storage
+            .interest_rate_params
+            .set(asset.clone(), params.clone());
+        Storage::set(env, &storage);
+    }
+
+    /// Set borrow rate smoothing half-life (seconds)
+    /// The effective rate closes half the gap to the target rate every half-life,
+    /// whatever the accrual cadence. Example: 3_600 = half the gap per hour; 0 disables smoothing
+    pub fn set_rate_half_life(env: &Env, half_life: u64) {
+        Self::require_admin(env);
+
+        // Longer half-lives would all but freeze the rate
+        if half_life > SECONDS_PER_YEAR {
+            panic_with_error!(env, Error::InvalidInterestRateParams);
+        }
+
+        let mut storage = Storage::get(env);
+        storage.rate_half_life = half_life;
+        Storage::set(env, &storage);
+    }
+
+    /// Set minimum partial fill for liquidation auctions (7 decimals)
+    /// Example: 1_000_000 = fills must take at least 10% of the original auction lot
+    /// Smaller fills (other than one closing out the auction) fail with `InvalidFillPercent`
+    pub fn set_min_fill_percent(env: &Env, min_fill_percent: u32) {
+        Self::require_admin(env);
+
+        if min_fill_percent > SCALAR_7 as u32 {
+            panic_with_error!(env, Error::InvalidFillPercent);
+        }
+
+        let mut storage = Storage::get(env);
+        storage.min_fill_percent = min_fill_percent;
+        Storage::set(env, &storage);
+    }
+
+    /// Set maximum total debt value per CDP (USD, same scale as borrow limits)
+    /// A value of 0 disables the cap
+    pub fn set_max_cdp_debt_value_usd(env: &Env, max_value: i128) {
+        Self::require_admin(env);
+
+        if max_value < 0 {
+            panic_with_error!(env, Error::NotPositive);
+        }
+
+        let mut storage = Storage::get(env);
+        storage.max_cdp_debt_value_usd = max_value;
+        Storage::set(env, &storage);
+    }
+
+    /// Get maximum total debt value per CDP (USD), 0 = unlimited
+    pub fn get_max_cdp_debt_value_usd(env: &Env) -> i128 {
+        let storage = Storage::get(env);
+        storage.max_cdp_debt_value_usd
+    }
+
+    /// Set maximum collateral value seized in one auction fill (USD, same scale as borrow limits)
+    /// Larger fills are scaled down so big lots are liquidated over several calls.
+    /// A value of 0 disables the cap
+    pub fn set_max_liq_seize_usd(env: &Env, max_value: i128) {
+        Self::require_admin(env);
+
+        if max_value < 0 {
+            panic_with_error!(env, Error::NotPositive);
+        }
+
+        let mut storage = Storage::get(env);
+        storage.max_liq_seize_usd = max_value;
+        Storage::set(env, &storage);
+    }
+
+    /// Get maximum collateral value seized in one auction fill (USD), 0 = unlimited
+    pub fn get_max_liq_seize_usd(env: &Env) -> i128 {
+        let storage = Storage::get(env);
+        storage.max_liq_seize_usd
+    }
+
+    /// Set interest-free window (seconds) opened by every borrow
+    /// Debt younger than the window accrues no interest and is left out of the
+    /// reserve's utilization and backstop take while it is covered. 0 = off
+    pub fn set_interest_grace_seconds(env: &Env, grace_seconds: u64) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        storage.interest_grace_seconds = grace_seconds;
+        Storage::set(env, &storage);
+    }
+
+    /// Get interest-free window (seconds) for new borrows, 0 = off
+    pub fn get_interest_grace_seconds(env: &Env) -> u64 {
+        let storage = Storage::get(env);
+        storage.interest_grace_seconds
+    }
+
+    /// Set maximum distinct bToken holders of a reserve
+    /// New suppliers beyond the cap are rejected; existing holders can still top up. 0 = unlimited
+    pub fn set_max_suppliers(env: &Env, asset: &Symbol, max_suppliers: u32) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        if max_suppliers > 0 {
+            storage.max_suppliers.set(asset.clone(), max_suppliers);
+        } else {
+            storage.max_suppliers.remove(asset.clone());
+        }
+        Storage::set(env, &storage);
+    }
+
+    /// Get maximum distinct bToken holders of a reserve, 0 = unlimited
+    pub fn get_max_suppliers(env: &Env, asset: &Symbol) -> u32 {
+        let storage = Storage::get(env);
+        storage.max_suppliers.get(asset.clone()).unwrap_or(0)
+    }
+
+    /// Set maximum distinct dToken holders of a reserve
+    /// New borrowers beyond the cap are rejected; existing holders can still top up. 0 = unlimited
+    pub fn set_max_borrowers(env: &Env, asset: &Symbol, max_borrowers: u32) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        if max_borrowers > 0 {
+            storage.max_borrowers.set(asset.clone(), max_borrowers);
+        } else {
+            storage.max_borrowers.remove(asset.clone());
+        }
+        Storage::set(env, &storage);
+    }
+
+    /// Get maximum distinct dToken holders of a reserve, 0 = unlimited
+    pub fn get_max_borrowers(env: &Env, asset: &Symbol) -> u32 {
+        let storage = Storage::get(env);
+        storage.max_borrowers.get(asset.clone()).unwrap_or(0)
+    }
+
+    /// Set maximum amount one borrower can draw from a reserve within a single ledger
+    /// Large positions must be built over several ledgers, giving price feeds time
+    /// to catch manipulation. The limit is cumulative: all of a borrower's borrows in one
+    /// ledger count toward it, so it is stricter than a cap on a single `borrow` call.
+    /// Borrows past the limit fail with `SingleBorrowLimitExceeded`. A value of 0 disables the limit
+    pub fn set_max_single_borrow(env: &Env, asset: &Symbol, max_amount: i128) {
+        Self::require_admin(env);
+
+        if max_amount < 0 {
+            panic_with_error!(env, Error::NotPositive);
+        }
+
+        let mut storage = Storage::get(env);
+        if max_amount > 0 {
+            storage.max_single_borrow.set(asset.clone(), max_amount);
+        } else {
+            storage.max_single_borrow.remove(asset.clone());
+        }
+        Storage::set(env, &storage);
+    }
+
+    /// Get maximum amount one borrower can draw from a reserve per ledger, 0 = unlimited
+    pub fn get_max_single_borrow(env: &Env, asset: &Symbol) -> i128 {
+        let storage = Storage::get(env);
+        storage.max_single_borrow.get(asset.clone()).unwrap_or(0)
+    }
+
+    /// Set maximum age (seconds) of an RWA token's oracle price for it to back new borrows
+    /// Tighter than the oracle's 24-hour hard limit; older prices make borrows against
+    /// the collateral revert with `OraclePriceStale`. A value of 0 disables the check
+    pub fn set_max_price_age(env: &Env, rwa_token: &Address, max_age: u64) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        if max_age > 0 {
+            storage.max_price_ages.set(rwa_token.clone(), max_age);
+        } else {
+            storage.max_price_ages.remove(rwa_token.clone());
+        }
+        Storage::set(env, &storage);
+    }
+
+    /// Get maximum oracle price age (seconds) for borrowing against an RWA token, 0 = off
+    pub fn get_max_price_age(env: &Env, rwa_token: &Address) -> u64 {
+        let storage = Storage::get(env);
+        storage.max_price_ages.get(rwa_token.clone()).unwrap_or(0)
+    }
+
+    /// Set whether a contract may deposit collateral on behalf of borrowers
+    /// Trusted depositors (e.g. the perps contract) call `deposit_collateral_for`
+    /// to credit a borrower's CDP directly from their own balance.
+    pub fn set_collateral_depositor(env: &Env, depositor: &Address, trusted: bool) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        if trusted {
+            storage.collateral_depositors.set(depositor.clone(), true);
+        } else {
+            storage.collateral_depositors.remove(depositor.clone());
+        }
         Storage::set(env, &storage);
     }
 
+    /// Get whether a contract may deposit collateral on behalf of borrowers
+    pub fn get_collateral_depositor(env: &Env, depositor: &Address) -> bool {
+        let storage = Storage::get(env);
+        storage
+            .collateral_depositors
+            .get(depositor.clone())
+            .unwrap_or(false)
+    }
+
+    /// Set whether a reserve is bootstrapping
+    /// Marks a reserve whose initial liquidity is being seeded before the oracle has a
+    /// price for it. Deposits never need a price; borrowing from any reserve, bootstrapping
+    /// or not, is rejected with `OraclePriceNotFound` until a price exists.
+    pub fn set_reserve_bootstrap(env: &Env, asset: &Symbol, bootstrap: bool) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        if bootstrap {
+            storage.bootstrap_reserves.set(asset.clone(), true);
+        } else {
+            storage.bootstrap_reserves.remove(asset.clone());
+        }
+        Storage::set(env, &storage);
+    }
+
+    /// Get whether a reserve is bootstrapping
+    pub fn is_reserve_bootstrap(env: &Env, asset: &Symbol) -> bool {
+        let storage = Storage::get(env);
+        storage
+            .bootstrap_reserves
+            .get(asset.clone())
+            .unwrap_or(false)
+    }
+
+    /// Get minimum partial fill for liquidation auctions (7 decimals)
+    pub fn get_min_fill_percent(env: &Env) -> u32 {
+        let storage = Storage::get(env);
+        storage.min_fill_percent
+    }
+
+    /// Get borrow rate smoothing half-life (seconds), 0 = off
+    pub fn get_rate_half_life(env: &Env) -> u64 {
+        let storage = Storage::get(env);
+        storage.rate_half_life
+    }
+
     /// Set pool state
     pub fn set_pool_state(env: &Env, state: PoolState) {
         Self::require_admin(env);
@@ -178,6 +463,7 @@ impl Admin {
     /// Only the admin can call this function
     pub fn upgrade(env: &Env, new_wasm_hash: &soroban_sdk::BytesN<32>) {
         Self::require_admin(env);
-        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
     }
 }
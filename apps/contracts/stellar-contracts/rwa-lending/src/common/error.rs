@@ -19,6 +19,7 @@ pub enum Error {
     PoolOnIce = 11,
     InsufficientPoolBalance = 12,
     InsufficientLiquidity = 13,
+    HolderCapReached = 14,
 
     // Lending errors
     InsufficientBTokenBalance = 20,
@@ -33,6 +34,8 @@ pub enum Error {
     CannotSwitchDebtAsset = 34,
     InsufficientDTokenBalance = 35,
     InsufficientDebtToRepay = 36,
+    CDPDebtCapExceeded = 37,
+    SingleBorrowLimitExceeded = 38,
 
     // Collateral errors
     CollateralNotFound = 40,
@@ -70,5 +73,10 @@ pub enum Error {
 
     // Token contract errors
     TokenContractNotSet = 84,
-}
 
+    // Reserve errors
+    OraclePriceNotFound = 85,
+
+    // Collateral price errors
+    OraclePriceStale = 86,
+}
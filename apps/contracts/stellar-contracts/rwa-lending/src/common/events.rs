@@ -1,4 +1,4 @@
-use soroban_sdk::{contractevent, Address, Symbol};
+use soroban_sdk::{Address, Symbol, contractevent};
 
 /// Events emitted by the lending pool contract
 #[contractevent]
@@ -33,6 +33,16 @@ pub struct RepayEvent {
     pub d_tokens: i128,
 }
 
+#[contractevent]
+pub struct RepayWithAssetEvent {
+    pub borrower: Address,
+    pub debt_asset: Symbol,
+    pub pay_asset: Symbol,
+    pub pay_amount: i128,
+    pub debt_amount: i128,
+    pub d_tokens: i128,
+}
+
 #[contractevent]
 pub struct AddCollateralEvent {
     pub borrower: Address,
@@ -40,6 +50,12 @@ pub struct AddCollateralEvent {
     pub amount: i128,
 }
 
+#[contractevent]
+pub struct CollateralStaleEvent {
+    pub rwa_token: Address,
+    pub price_timestamp: u64,
+}
+
 #[contractevent]
 pub struct RemoveCollateralEvent {
     pub borrower: Address,
@@ -173,6 +189,26 @@ impl Events {
         .publish(env);
     }
 
+    pub fn repay_with_asset(
+        env: &soroban_sdk::Env,
+        borrower: &Address,
+        debt_asset: &Symbol,
+        pay_asset: &Symbol,
+        pay_amount: i128,
+        debt_amount: i128,
+        d_tokens: i128,
+    ) {
+        RepayWithAssetEvent {
+            borrower: borrower.clone(),
+            debt_asset: debt_asset.clone(),
+            pay_asset: pay_asset.clone(),
+            pay_amount,
+            debt_amount,
+            d_tokens,
+        }
+        .publish(env);
+    }
+
     pub fn add_collateral(
         env: &soroban_sdk::Env,
         borrower: &Address,
@@ -201,6 +237,14 @@ impl Events {
         .publish(env);
     }
 
+    pub fn collateral_stale(env: &soroban_sdk::Env, rwa_token: &Address, price_timestamp: u64) {
+        CollateralStaleEvent {
+            rwa_token: rwa_token.clone(),
+            price_timestamp,
+        }
+        .publish(env);
+    }
+
     pub fn liquidation_initiated(
         env: &soroban_sdk::Env,
         borrower: &Address,
@@ -317,4 +361,3 @@ impl Events {
         .publish(env);
     }
 }
-
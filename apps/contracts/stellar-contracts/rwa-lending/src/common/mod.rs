@@ -2,4 +2,3 @@ pub mod error;
 pub mod events;
 pub mod storage;
 pub mod types;
-
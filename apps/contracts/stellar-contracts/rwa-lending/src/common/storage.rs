@@ -1,10 +1,9 @@
-use soroban_sdk::{panic_with_error, Address, Env, Map, Symbol, Vec};
+use soroban_sdk::{Address, Env, Map, Symbol, Vec, panic_with_error, symbol_short};
 
 use crate::common::error::Error;
 use crate::common::types::{
-    AuctionData, BackstopDeposit, CDP, InterestRateParams, PoolState,
-    ReserveData, WithdrawalRequest, ADMIN_KEY, STORAGE,
-    INSTANCE_TTL, INSTANCE_BUMP, USER_TTL, USER_BUMP,
+    ADMIN_KEY, AuctionData, BackstopDeposit, CDP, INSTANCE_BUMP, INSTANCE_TTL, InterestGrace,
+    InterestRateParams, PoolState, ReserveData, STORAGE, USER_BUMP, USER_TTL, WithdrawalRequest,
 };
 
 /// Main pool storage structure
@@ -20,18 +19,22 @@ pub struct PoolStorage {
 
     // Lending (bTokens) - User balances
     pub b_token_balances: Map<Address, Map<Symbol, i128>>, // bTokens per lender
+    pub supplier_counts: Map<Symbol, u32>,                 // Distinct bToken holders per reserve
 
     // Borrowing (dTokens) - User balances (single asset per borrower)
     pub d_token_balances: Map<Address, Map<Symbol, i128>>, // dTokens per borrower
+    pub borrower_counts: Map<Symbol, u32>,                 // Distinct dToken holders per reserve
 
     // Collateral
     pub collateral: Map<Address, Map<Address, i128>>, // RWA tokens per borrower
 
     // Interest Rate Parameters
     pub interest_rate_params: Map<Symbol, InterestRateParams>,
+    pub rate_half_life: u64, // Seconds for the borrow rate to close half the gap to its target, 0 = no smoothing
 
     // Auctions (unified structure for all auction types)
     pub auction_data: Map<u32, AuctionData>,
+    pub min_fill_percent: u32, // Minimum partial liquidation fill, of the original lot (7 decimals), 0 = no minimum
 
     // Backstop
     pub backstop_deposits: Map<Address, BackstopDeposit>,
@@ -48,6 +51,17 @@ pub struct PoolStorage {
     // Admin
     pub admin: Address,
     pub collateral_factors: Map<Address, u32>, // Collateral factor per RWA token (7 decimals)
+    pub liquidation_thresholds: Map<Address, u32>, // Liquidation threshold per RWA token (7 decimals)
+    pub max_cdp_debt_value_usd: i128, // Maximum debt value per CDP (USD), 0 = unlimited
+    pub max_liq_seize_usd: i128, // Maximum collateral value seized per fill (USD), 0 = unlimited
+    pub interest_grace_seconds: u64, // Interest-free window after borrowing into an empty CDP, 0 = off
+    pub bootstrap_reserves: Map<Symbol, bool>, // Reserves accepting supply before the oracle has a price
+    pub max_suppliers: Map<Symbol, u32>,       // Maximum distinct bToken holders, per reserve
+    pub max_borrowers: Map<Symbol, u32>,       // Maximum distinct dToken holders, per reserve
+    pub max_single_borrow: Map<Symbol, i128>, // Maximum amount one borrower can draw per ledger, per reserve
+    pub max_price_ages: Map<Address, u64>, // Maximum collateral price age (seconds) for new borrows, per RWA token
+    pub stale_collateral: Map<Address, u64>, // RWA token -> price timestamp flagged stale, until the oracle refreshes
+    pub collateral_depositors: Map<Address, bool>, // Contracts allowed to deposit collateral on behalf of borrowers
 
     // Token contracts mapping: Symbol -> Address
     pub token_contracts: Map<Symbol, Address>,
@@ -128,10 +142,7 @@ impl Storage {
 
     /// Get CDP for a borrower
     pub fn get_cdp(env: &Env, borrower: &Address) -> Option<CDP> {
-        let cdp: Option<CDP> = env.storage()
-            .persistent()
-            .get(borrower)
-            .unwrap_or(None);
+        let cdp: Option<CDP> = env.storage().persistent().get(borrower).unwrap_or(None);
 
         // Extend TTL if CDP exists
         if cdp.is_some() {
@@ -151,6 +162,49 @@ impl Storage {
             .extend_ttl(borrower, USER_TTL, USER_BUMP);
     }
 
+    /// Get a borrower's open interest-free windows, oldest first (persistent, with TTL)
+    pub fn get_interest_graces(env: &Env, borrower: &Address) -> Vec<InterestGrace> {
+        let key = (symbol_short!("grace"), borrower.clone());
+        let graces: Option<Vec<InterestGrace>> = env.storage().persistent().get(&key);
+        match graces {
+            Some(graces) => {
+                env.storage()
+                    .persistent()
+                    .extend_ttl(&key, USER_TTL, USER_BUMP);
+                graces
+            }
+            None => Vec::new(env),
+        }
+    }
+
+    /// Set a borrower's open interest-free windows (persistent, with TTL; removed when empty)
+    pub fn set_interest_graces(env: &Env, borrower: &Address, graces: &Vec<InterestGrace>) {
+        let key = (symbol_short!("grace"), borrower.clone());
+        if graces.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, graces);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, USER_TTL, USER_BUMP);
+        }
+    }
+
+    /// Get the amount a borrower drew in a ledger as (sequence, amount) (persistent, with TTL)
+    pub fn get_ledger_borrow(env: &Env, borrower: &Address) -> Option<(u32, i128)> {
+        let key = (symbol_short!("blk_brw"), borrower.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set the amount a borrower drew in a ledger (persistent, with TTL)
+    pub fn set_ledger_borrow(env: &Env, borrower: &Address, sequence: u32, amount: i128) {
+        let key = (symbol_short!("blk_brw"), borrower.clone());
+        env.storage().persistent().set(&key, &(sequence, amount));
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, USER_TTL, USER_BUMP);
+    }
+
     // ========== bToken Operations ==========
 
     /// Get bToken balance for a lender
@@ -171,11 +225,21 @@ impl Storage {
             .b_token_balances
             .get(lender.clone())
             .unwrap_or(Map::new(env));
+        let previous = lender_balances.get(asset.clone()).unwrap_or(0);
         lender_balances.set(asset.clone(), amount);
-        storage.b_token_balances.set(lender.clone(), lender_balances);
+        storage
+            .b_token_balances
+            .set(lender.clone(), lender_balances);
+        Self::adjust_holder_count(&mut storage.supplier_counts, asset, previous, amount);
         Self::set(env, &storage);
     }
 
+    /// Get the number of distinct bToken holders for an asset
+    pub fn get_supplier_count(env: &Env, asset: &Symbol) -> u32 {
+        let storage = Self::get(env);
+        storage.supplier_counts.get(asset.clone()).unwrap_or(0)
+    }
+
     /// Get bTokenRate for an asset (12 decimals)
     pub fn get_b_token_rate(env: &Env, asset: &Symbol) -> i128 {
         let reserve = Self::get_reserve_data(env, asset);
@@ -215,11 +279,36 @@ impl Storage {
             .d_token_balances
             .get(borrower.clone())
             .unwrap_or(Map::new(env));
+        let previous = borrower_balances.get(asset.clone()).unwrap_or(0);
         borrower_balances.set(asset.clone(), amount);
-        storage.d_token_balances.set(borrower.clone(), borrower_balances);
+        storage
+            .d_token_balances
+            .set(borrower.clone(), borrower_balances);
+        Self::adjust_holder_count(&mut storage.borrower_counts, asset, previous, amount);
         Self::set(env, &storage);
     }
 
+    /// Get the number of distinct dToken holders for an asset
+    pub fn get_borrower_count(env: &Env, asset: &Symbol) -> u32 {
+        let storage = Self::get(env);
+        storage.borrower_counts.get(asset.clone()).unwrap_or(0)
+    }
+
+    /// Count a holder in when their balance leaves zero and out when it returns to zero
+    fn adjust_holder_count(
+        counts: &mut Map<Symbol, u32>,
+        asset: &Symbol,
+        previous: i128,
+        amount: i128,
+    ) {
+        let holders = counts.get(asset.clone()).unwrap_or(0);
+        if previous == 0 && amount > 0 {
+            counts.set(asset.clone(), holders + 1);
+        } else if previous > 0 && amount == 0 {
+            counts.set(asset.clone(), holders.saturating_sub(1));
+        }
+    }
+
     /// Get dTokenRate for an asset (12 decimals)
     pub fn get_d_token_rate(env: &Env, asset: &Symbol) -> i128 {
         let reserve = Self::get_reserve_data(env, asset);
@@ -260,7 +349,9 @@ impl Storage {
             .get(borrower.clone())
             .unwrap_or(Map::new(env));
         borrower_collateral.set(rwa_token.clone(), amount);
-        storage.collateral.set(borrower.clone(), borrower_collateral);
+        storage
+            .collateral
+            .set(borrower.clone(), borrower_collateral);
         Self::set(env, &storage);
     }
 
@@ -290,8 +381,9 @@ impl Storage {
     /// Set token contract address for an asset symbol
     pub fn set_token_contract(env: &Env, asset: &Symbol, token_address: &Address) {
         let mut storage = Self::get(env);
-        storage.token_contracts.set(asset.clone(), token_address.clone());
+        storage
+            .token_contracts
+            .set(asset.clone(), token_address.clone());
         Self::set(env, &storage);
     }
-
 }
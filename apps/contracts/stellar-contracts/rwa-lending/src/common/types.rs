@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, Map, Symbol};
+use soroban_sdk::{Address, Map, Symbol, contracttype};
 
 // ============================================================================
 // SCALAR CONSTANTS
@@ -8,6 +8,10 @@ use soroban_sdk::{contracttype, Address, Map, Symbol};
 /// Example: 75% = 7_500_000, 1% = 100_000
 pub const SCALAR_7: i128 = 10_000_000;
 
+/// Decimals of every USD value (borrow limits, debt values, USD caps)
+/// Oracle prices and token amounts are normalized to this scale
+pub const USD_DECIMALS: u32 = 7;
+
 /// 12 decimals - Used for bToken/dToken rates (exchange rates)
 /// Example: 1:1 rate = 1_000_000_000_000
 pub const SCALAR_12: i128 = 1_000_000_000_000;
@@ -15,6 +19,9 @@ pub const SCALAR_12: i128 = 1_000_000_000_000;
 /// Seconds per year for interest calculations
 pub const SECONDS_PER_YEAR: u64 = 31_536_000; // 365 days
 
+/// ln(2) in 7 decimals - Used for half-life rate smoothing
+pub const LN_2: i128 = 6_931_472;
+
 // ============================================================================
 // TTL CONSTANTS
 // ============================================================================
@@ -78,9 +85,9 @@ pub const BAD_DEBT_LOT_MULTIPLIER: i128 = 12_000_000;
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum PoolState {
-    Active,  // All operations enabled
-    OnIce,   // Only borrowing disabled
-    Frozen,  // Both borrowing and depositing disabled
+    Active, // All operations enabled
+    OnIce,  // Only borrowing disabled
+    Frozen, // Both borrowing and depositing disabled
 }
 
 // ============================================================================
@@ -160,19 +167,30 @@ pub struct ReserveData {
 
     /// Last interest accrual timestamp
     pub last_time: u64,
+
+    /// Effective borrow rate applied at the last accrual (7 decimals)
+    /// Used as the starting point for rate smoothing
+    pub last_rate: i128,
+
+    /// dTokens of borrows still inside their interest-free window
+    /// Excluded from utilization and the backstop take, so their interest is never credited;
+    /// a closed window is released the next time its borrower's position is settled
+    pub grace_d_supply: i128,
 }
 
 impl ReserveData {
     /// Create new reserve data with initial 1:1 rates
     pub fn new(timestamp: u64) -> Self {
         Self {
-            b_rate: SCALAR_12,  // 1:1 initial rate
-            d_rate: SCALAR_12,  // 1:1 initial rate
-            ir_mod: SCALAR_7,   // 1.0 initial modifier
+            b_rate: SCALAR_12, // 1:1 initial rate
+            d_rate: SCALAR_12, // 1:1 initial rate
+            ir_mod: SCALAR_7,  // 1.0 initial modifier
             b_supply: 0,
             d_supply: 0,
             backstop_credit: 0,
             last_time: timestamp,
+            last_rate: 0,
+            grace_d_supply: 0,
         }
     }
 }
@@ -201,6 +219,25 @@ pub struct CDP {
     pub last_update: u64,
 }
 
+/// Interest-free window opened by a single borrow
+///
+/// Dropped once the window closes and its last rebate is settled.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct InterestGrace {
+    /// Timestamp at which the window closes
+    pub ends_at: u64,
+
+    /// Timestamp up to which the rebate has been settled
+    pub settled_at: u64,
+
+    /// Pool dToken rate at `settled_at` (12 decimals)
+    pub d_token_rate: i128,
+
+    /// dTokens minted by the borrow that are still covered (capped at the debt)
+    pub d_tokens: i128,
+}
+
 // ============================================================================
 // AUCTION TYPES
 // ============================================================================
@@ -242,6 +279,9 @@ pub struct AuctionData {
     /// For Interest: interest tokens
     pub lot: Map<Address, i128>,
 
+    /// Lot at auction creation; `lot` holds what partial fills have left
+    pub initial_lot: Map<Address, i128>,
+
     /// Auction start block
     pub block: u32,
 }
@@ -319,9 +359,7 @@ pub mod rounding {
             .ok_or(Error::ArithmeticError)?
             .checked_sub(1)
             .ok_or(Error::ArithmeticError)?;
-        numerator
-            .checked_div(b_rate)
-            .ok_or(Error::ArithmeticError)
+        numerator.checked_div(b_rate).ok_or(Error::ArithmeticError)
     }
 
     /// Convert bTokens to underlying asset amount with rounding down (floor)
@@ -346,9 +384,7 @@ pub mod rounding {
             .ok_or(Error::ArithmeticError)?
             .checked_sub(1)
             .ok_or(Error::ArithmeticError)?;
-        numerator
-            .checked_div(d_rate)
-            .ok_or(Error::ArithmeticError)
+        numerator.checked_div(d_rate).ok_or(Error::ArithmeticError)
     }
 
     /// Convert underlying asset amount to dTokens with rounding down (floor)
@@ -1,4 +1,4 @@
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
+use soroban_sdk::{Address, Env, Symbol, contract, contractimpl};
 
 use crate::admin::Admin;
 use crate::common::error::Error;
@@ -45,15 +45,41 @@ impl LendingContract {
         Admin::set_collateral_factor(&env, &rwa_token, factor);
     }
 
+    /// Set liquidation threshold for an RWA token (>= collateral factor)
+    pub fn set_liquidation_threshold(env: Env, rwa_token: Address, threshold: u32) {
+        Admin::set_liquidation_threshold(&env, &rwa_token, threshold);
+    }
+
     /// Set interest rate parameters for an asset
-    pub fn set_interest_rate_params(
-        env: Env,
-        asset: Symbol,
-        params: InterestRateParams,
-    ) {
+    pub fn set_interest_rate_params(env: Env, asset: Symbol, params: InterestRateParams) {
         Admin::set_interest_rate_params(&env, &asset, &params);
     }
 
+    /// Set borrow rate smoothing half-life (seconds)
+    pub fn set_rate_half_life(env: Env, half_life: u64) {
+        Admin::set_rate_half_life(&env, half_life);
+    }
+
+    /// Set minimum partial fill for liquidation auctions
+    pub fn set_min_fill_percent(env: Env, min_fill_percent: u32) {
+        Admin::set_min_fill_percent(&env, min_fill_percent);
+    }
+
+    /// Set maximum total debt value per CDP (0 = unlimited)
+    pub fn set_max_cdp_debt_value_usd(env: Env, max_value: i128) {
+        Admin::set_max_cdp_debt_value_usd(&env, max_value);
+    }
+
+    /// Set maximum collateral value seized per auction fill (0 = unlimited)
+    pub fn set_max_liq_seize_usd(env: Env, max_value: i128) {
+        Admin::set_max_liq_seize_usd(&env, max_value);
+    }
+
+    /// Set interest-free window (seconds) applied to each new borrow (0 = off)
+    pub fn set_interest_grace_seconds(env: Env, grace_seconds: u64) {
+        Admin::set_interest_grace_seconds(&env, grace_seconds);
+    }
+
     /// Set pool state
     pub fn set_pool_state(env: Env, state: PoolState) {
         Admin::set_pool_state(&env, state);
@@ -79,6 +105,36 @@ impl LendingContract {
         Admin::set_backstop_token(&env, &token_address);
     }
 
+    /// Set maximum distinct suppliers of a reserve (0 = unlimited)
+    pub fn set_max_suppliers(env: Env, asset: Symbol, max_suppliers: u32) {
+        Admin::set_max_suppliers(&env, &asset, max_suppliers);
+    }
+
+    /// Set maximum distinct borrowers of a reserve (0 = unlimited)
+    pub fn set_max_borrowers(env: Env, asset: Symbol, max_borrowers: u32) {
+        Admin::set_max_borrowers(&env, &asset, max_borrowers);
+    }
+
+    /// Set maximum amount one borrower can draw from a reserve per ledger (0 = unlimited)
+    pub fn set_max_single_borrow(env: Env, asset: Symbol, max_amount: i128) {
+        Admin::set_max_single_borrow(&env, &asset, max_amount);
+    }
+
+    /// Set maximum oracle price age (seconds) for borrowing against an RWA token (0 = off)
+    pub fn set_max_price_age(env: Env, rwa_token: Address, max_age: u64) {
+        Admin::set_max_price_age(&env, &rwa_token, max_age);
+    }
+
+    /// Allow or revoke a contract depositing collateral on behalf of borrowers
+    pub fn set_collateral_depositor(env: Env, depositor: Address, trusted: bool) {
+        Admin::set_collateral_depositor(&env, &depositor, trusted);
+    }
+
+    /// Mark a reserve as seeding liquidity before the oracle has a price for it
+    pub fn set_reserve_bootstrap(env: Env, asset: Symbol, bootstrap: bool) {
+        Admin::set_reserve_bootstrap(&env, &asset, bootstrap);
+    }
+
     /// Upgrade the contract to a new WASM hash
     /// Only the admin can call this function
     pub fn upgrade(env: Env, new_wasm_hash: soroban_sdk::BytesN<32>) {
@@ -93,7 +149,12 @@ impl LendingContract {
     }
 
     /// Withdraw crypto asset from the pool
-    pub fn withdraw(env: Env, lender: Address, asset: Symbol, b_tokens: i128) -> Result<i128, Error> {
+    pub fn withdraw(
+        env: Env,
+        lender: Address,
+        asset: Symbol,
+        b_tokens: i128,
+    ) -> Result<i128, Error> {
         Lending::withdraw(&env, &lender, &asset, b_tokens)
     }
 
@@ -120,15 +181,36 @@ impl LendingContract {
     }
 
     /// Repay debt
-    pub fn repay(env: Env, borrower: Address, asset: Symbol, d_tokens: i128) -> Result<i128, Error> {
+    pub fn repay(
+        env: Env,
+        borrower: Address,
+        asset: Symbol,
+        d_tokens: i128,
+    ) -> Result<i128, Error> {
         Borrowing::repay(&env, &borrower, &asset, d_tokens)
     }
 
+    /// Repay debt with a different asset, converted at the oracle cross-rate
+    pub fn repay_with_asset(
+        env: Env,
+        borrower: Address,
+        debt_asset: Symbol,
+        pay_asset: Symbol,
+        pay_amount: i128,
+    ) -> Result<i128, Error> {
+        Borrowing::repay_with_asset(&env, &borrower, &debt_asset, &pay_asset, pay_amount)
+    }
+
     /// Get dToken balance for a borrower
     pub fn get_d_token_balance(env: Env, borrower: Address, asset: Symbol) -> i128 {
         Borrowing::get_d_token_balance(&env, &borrower, &asset)
     }
 
+    /// Get a borrower's outstanding debt in the debt asset (net of any interest-free window)
+    pub fn get_debt_amount(env: Env, borrower: Address) -> Result<i128, Error> {
+        Borrowing::get_debt_amount(&env, &borrower)
+    }
+
     /// Get dTokenRate for an asset
     pub fn get_d_token_rate(env: Env, asset: Symbol) -> i128 {
         Borrowing::get_d_token_rate(&env, &asset)
@@ -151,6 +233,17 @@ impl LendingContract {
         Collateral::add_collateral(&env, &borrower, &rwa_token, amount)
     }
 
+    /// Add collateral for a borrower from a trusted depositor contract
+    pub fn deposit_collateral_for(
+        env: Env,
+        depositor: Address,
+        borrower: Address,
+        rwa_token: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        Collateral::deposit_collateral_for(&env, &depositor, &borrower, &rwa_token, amount)
+    }
+
     /// Remove RWA token collateral
     pub fn remove_collateral(
         env: Env,
@@ -166,6 +259,17 @@ impl LendingContract {
         Collateral::get_collateral(&env, &borrower, &rwa_token)
     }
 
+    /// Flag an RWA token's collateral as unusable for new borrows while its price is stale
+    /// Callable by anyone (keepers); cleared once the oracle publishes a newer price
+    pub fn flag_stale_collateral(env: Env, rwa_token: Address) -> Result<bool, Error> {
+        Collateral::flag_stale_collateral(&env, &rwa_token)
+    }
+
+    /// Whether an RWA token is flagged stale and still awaiting a fresh price
+    pub fn is_collateral_stale(env: Env, rwa_token: Address) -> bool {
+        Collateral::is_collateral_stale(&env, &rwa_token)
+    }
+
     // ========== Interest Functions ==========
 
     /// Get current interest rate for an asset
@@ -188,16 +292,33 @@ impl LendingContract {
         debt_asset: Symbol,
         liquidation_percent: u32,
     ) -> Result<u32, Error> {
-        Liquidations::initiate_liquidation(&env, &borrower, &rwa_token, &debt_asset, liquidation_percent)
+        Liquidations::initiate_liquidation(
+            &env,
+            &borrower,
+            &rwa_token,
+            &debt_asset,
+            liquidation_percent,
+        )
     }
 
-    /// Fill a liquidation auction
+    /// Fill a liquidation auction (fill_percent in 7 decimals, SCALAR_7 = full fill)
     pub fn fill_auction(
         env: Env,
         auction_id: u32,
         liquidator: Address,
-    ) -> Result<(), Error> {
-        Liquidations::fill_auction(&env, auction_id, &liquidator)
+        fill_percent: i128,
+    ) -> Result<(i128, i128), Error> {
+        Liquidations::fill_auction(&env, auction_id, &liquidator, fill_percent)
+    }
+
+    /// Simulate a partial fill of a liquidation auction
+    /// Returns (collateral_received, debt_to_pay)
+    pub fn simulate_partial_fill(
+        env: Env,
+        auction_id: u32,
+        fill_percent: i128,
+    ) -> Result<(i128, i128), Error> {
+        Liquidations::simulate_partial_fill(&env, auction_id, fill_percent)
     }
 
     // ========== Backstop Functions ==========
@@ -283,9 +404,78 @@ impl LendingContract {
         Admin::get_collateral_factor(&env, &rwa_token)
     }
 
+    /// Get liquidation threshold for an RWA token
+    pub fn get_liquidation_threshold(env: Env, rwa_token: Address) -> u32 {
+        Admin::get_liquidation_threshold(&env, &rwa_token)
+    }
+
+    /// Get maximum total debt value per CDP (0 = unlimited)
+    pub fn get_max_cdp_debt_value_usd(env: Env) -> i128 {
+        Admin::get_max_cdp_debt_value_usd(&env)
+    }
+
+    /// Get maximum collateral value seized per auction fill (0 = unlimited)
+    pub fn get_max_liq_seize_usd(env: Env) -> i128 {
+        Admin::get_max_liq_seize_usd(&env)
+    }
+
+    /// Get interest-free window (seconds) applied to each new borrow (0 = off)
+    pub fn get_interest_grace_seconds(env: Env) -> u64 {
+        Admin::get_interest_grace_seconds(&env)
+    }
+
+    /// Get maximum distinct suppliers of a reserve (0 = unlimited)
+    pub fn get_max_suppliers(env: Env, asset: Symbol) -> u32 {
+        Admin::get_max_suppliers(&env, &asset)
+    }
+
+    /// Get maximum distinct borrowers of a reserve (0 = unlimited)
+    pub fn get_max_borrowers(env: Env, asset: Symbol) -> u32 {
+        Admin::get_max_borrowers(&env, &asset)
+    }
+
+    /// Get number of distinct bToken holders for an asset
+    pub fn get_supplier_count(env: Env, asset: Symbol) -> u32 {
+        Storage::get_supplier_count(&env, &asset)
+    }
+
+    /// Get number of distinct dToken holders for an asset
+    pub fn get_borrower_count(env: Env, asset: Symbol) -> u32 {
+        Storage::get_borrower_count(&env, &asset)
+    }
+
+    /// Get maximum amount one borrower can draw from a reserve per ledger (0 = unlimited)
+    pub fn get_max_single_borrow(env: Env, asset: Symbol) -> i128 {
+        Admin::get_max_single_borrow(&env, &asset)
+    }
+
+    /// Get maximum oracle price age (seconds) for borrowing against an RWA token (0 = off)
+    pub fn get_max_price_age(env: Env, rwa_token: Address) -> u64 {
+        Admin::get_max_price_age(&env, &rwa_token)
+    }
+
+    /// Get whether a contract may deposit collateral on behalf of borrowers
+    pub fn get_collateral_depositor(env: Env, depositor: Address) -> bool {
+        Admin::get_collateral_depositor(&env, &depositor)
+    }
+
+    /// Get whether a reserve accepts deposits before it has a price
+    pub fn is_reserve_bootstrap(env: Env, asset: Symbol) -> bool {
+        Admin::is_reserve_bootstrap(&env, &asset)
+    }
+
+    /// Get minimum partial fill for liquidation auctions
+    pub fn get_min_fill_percent(env: Env) -> u32 {
+        Admin::get_min_fill_percent(&env)
+    }
+
+    /// Get borrow rate smoothing half-life (seconds)
+    pub fn get_rate_half_life(env: Env) -> u64 {
+        Admin::get_rate_half_life(&env)
+    }
+
     /// Calculate health factor for a borrower (7 decimals)
     pub fn calculate_health_factor(env: Env, borrower: Address) -> Result<u32, Error> {
         Liquidations::calculate_health_factor(&env, &borrower)
     }
 }
-
@@ -1,10 +1,12 @@
-use soroban_sdk::{assert_with_error, Address, Env, Symbol, token::TokenClient};
+use soroban_sdk::{Address, Env, Symbol, Vec, assert_with_error, token::TokenClient};
 
 use crate::admin::Admin;
 use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::common::storage::Storage;
-use crate::common::types::{self, MIN_HEALTH_FACTOR, PoolState, SCALAR_7, SCALAR_12};
+use crate::common::types::{
+    self, InterestGrace, MIN_HEALTH_FACTOR, PoolState, SCALAR_7, SCALAR_12, USD_DECIMALS,
+};
 use crate::operations::collateral::Collateral;
 use crate::operations::interest::Interest;
 use crate::operations::oracles::Oracles;
@@ -31,68 +33,61 @@ impl Borrowing {
             return Err(Error::PoolOnIce);
         }
 
-        // Accrue interest before borrow
+        // Reserves can be supplied before a price exists (e.g. while bootstrapping), but never borrowed
+        if !Oracles::has_crypto_price(env, asset) {
+            return Err(Error::OraclePriceNotFound);
+        }
+
+        // Accrue interest and settle any interest-free window before borrow
         Interest::accrue_interest(env, asset)?;
+        Self::settle_interest_grace(env, borrower, asset)?;
 
         // Get or create CDP
-        let mut cdp = Storage::get_cdp(env, borrower).unwrap_or_else(|| {
-            crate::common::types::CDP {
+        let mut cdp =
+            Storage::get_cdp(env, borrower).unwrap_or_else(|| crate::common::types::CDP {
                 collateral: soroban_sdk::Map::new(env),
                 debt_asset: None,
                 d_tokens: 0,
                 created_at: env.ledger().timestamp(),
                 last_update: env.ledger().timestamp(),
-            }
-        });
+            });
 
         // Check if borrower already has debt in a different asset
         if let Some(debt_asset) = &cdp.debt_asset
-            && debt_asset != asset {
-                return Err(Error::DebtAssetAlreadySet);
+            && debt_asset != asset
+        {
+            return Err(Error::DebtAssetAlreadySet);
+        }
+
+        // New borrows need a fresh price for every collateral backing them
+        let all_collateral = Collateral::get_all_collateral(env, borrower);
+        for (rwa_token, collateral_amount) in all_collateral.iter() {
+            if collateral_amount > 0 {
+                Oracles::require_fresh_rwa_price(env, &rwa_token)?;
             }
+        }
 
         // Calculate borrow limit
         let borrow_limit = Self::calculate_borrow_limit(env, borrower)?;
 
-        // Get asset decimals from token contract
-        let token_address = Storage::get_token_contract(env, asset)
-            .ok_or(Error::TokenContractNotSet)?;
-        let token_client = TokenClient::new(env, &token_address);
-        let asset_decimals = token_client.decimals();
-
         // Get current debt value
         let current_debt_value = if cdp.d_tokens > 0 {
             let d_token_rate = Storage::get_d_token_rate(env, asset);
-            let debt_amount = cdp.d_tokens
+            let debt_amount = cdp
+                .d_tokens
                 .checked_mul(d_token_rate)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(SCALAR_12)
                 .ok_or(Error::ArithmeticError)?;
 
-            // Get price of debt asset (includes price decimals from oracle)
-            let (debt_price, debt_price_decimals) = Oracles::get_crypto_price_with_decimals(env, asset)?;
-
             // Calculate debt value in USD
-            Oracles::calculate_usd_value(
-                env,
-                debt_amount,
-                debt_price,
-                asset_decimals,
-                debt_price_decimals,
-            )?
+            Oracles::crypto_usd_value(env, asset, debt_amount)?
         } else {
             0
         };
 
         // Calculate new debt value
-        let (asset_price, price_decimals) = Oracles::get_crypto_price_with_decimals(env, asset)?;
-        let new_debt_value = Oracles::calculate_usd_value(
-            env,
-            amount,
-            asset_price,
-            asset_decimals,
-            price_decimals,
-        )?;
+        let new_debt_value = Oracles::crypto_usd_value(env, asset, amount)?;
 
         let total_debt_value = current_debt_value
             .checked_add(new_debt_value)
@@ -102,19 +97,49 @@ impl Borrowing {
             return Err(Error::InsufficientBorrowLimit);
         }
 
+        // Enforce the per-CDP debt cap (0 = unlimited)
+        let max_cdp_debt_value = Storage::get(env).max_cdp_debt_value_usd;
+        if max_cdp_debt_value > 0 && total_debt_value > max_cdp_debt_value {
+            return Err(Error::CDPDebtCapExceeded);
+        }
+
+        // Enforce the per-ledger borrow limit for this reserve (0 = unlimited); every
+        // borrow by this borrower in the current ledger counts toward the same limit
+        let max_single_borrow = Admin::get_max_single_borrow(env, asset);
+        let sequence = env.ledger().sequence();
+        if max_single_borrow > 0 {
+            let borrowed_this_ledger = match Storage::get_ledger_borrow(env, borrower) {
+                Some((borrow_sequence, borrowed)) if borrow_sequence == sequence => borrowed,
+                _ => 0,
+            }
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticError)?;
+
+            if borrowed_this_ledger > max_single_borrow {
+                return Err(Error::SingleBorrowLimitExceeded);
+            }
+            Storage::set_ledger_borrow(env, borrower, sequence, borrowed_this_ledger);
+        }
+
         // Check pool has enough balance
         let pool_balance = Storage::get_pool_balance(env, asset);
         if pool_balance < amount {
             return Err(Error::InsufficientPoolBalance);
         }
 
-        // Get current dTokenRate (12 decimals)
-        let d_token_rate = Storage::get_d_token_rate(env, asset);
-
         // Calculate dTokens with rounding up
         // This favors the protocol by minting more dTokens
+        let d_token_rate = Storage::get_d_token_rate(env, asset);
         let d_tokens = types::rounding::to_d_token_up(amount, d_token_rate)?;
 
+        // A new borrower needs a free holder slot in this reserve
+        if Storage::get_d_token_balance(env, borrower, asset) == 0 {
+            let max_borrowers = Admin::get_max_borrowers(env, asset);
+            if max_borrowers > 0 && Storage::get_borrower_count(env, asset) >= max_borrowers {
+                return Err(Error::HolderCapReached);
+            }
+        }
+
         // Update CDP
         cdp.debt_asset = Some(asset.clone());
         cdp.d_tokens += d_tokens;
@@ -129,6 +154,12 @@ impl Borrowing {
         let current_supply = Storage::get_d_token_supply(env, asset);
         Storage::set_d_token_supply(env, asset, current_supply + d_tokens);
 
+        // Each borrow opens its own interest-free window
+        let grace_seconds = Storage::get(env).interest_grace_seconds;
+        if grace_seconds > 0 {
+            Self::open_interest_grace(env, borrower, asset, d_tokens, d_token_rate, grace_seconds)?;
+        }
+
         // Update pool balance
         Storage::set_pool_balance(env, asset, pool_balance - amount);
 
@@ -141,14 +172,15 @@ impl Borrowing {
 
         // Verify health factor remains above minimum threshold (7 decimals)
         // This ensures the borrower maintains a safety margin above liquidation threshold
-        let health_factor = crate::operations::liquidations::Liquidations::calculate_health_factor(env, borrower)?;
+        let health_factor =
+            crate::operations::liquidations::Liquidations::calculate_health_factor(env, borrower)?;
         if (health_factor as i128) < MIN_HEALTH_FACTOR {
             return Err(Error::HealthFactorTooLow);
         }
 
         // Transfer asset from pool to borrower
-        let token_address = Storage::get_token_contract(env, asset)
-            .ok_or(Error::TokenContractNotSet)?;
+        let token_address =
+            Storage::get_token_contract(env, asset).ok_or(Error::TokenContractNotSet)?;
         let token_client = TokenClient::new(env, &token_address);
         token_client.transfer(&env.current_contract_address(), borrower, &amount);
 
@@ -169,34 +201,35 @@ impl Borrowing {
 
         assert_with_error!(env, d_tokens > 0, Error::NotPositive);
 
-        // Accrue interest before repay
+        // Accrue interest and settle any interest-free window before repay
         Interest::accrue_interest(env, asset)?;
+        Self::settle_interest_grace(env, borrower, asset)?;
 
         // Get CDP
-        let mut cdp = Storage::get_cdp(env, borrower)
-            .ok_or(Error::DebtAssetNotSet)?;
+        let mut cdp = Storage::get_cdp(env, borrower).ok_or(Error::DebtAssetNotSet)?;
 
         // Check debt asset matches
         if cdp.debt_asset.as_ref() != Some(asset) {
             return Err(Error::DebtAssetNotSet);
         }
 
-        // Check borrower has enough dTokens
-        let borrower_balance = Storage::get_d_token_balance(env, borrower, asset);
-        if borrower_balance < d_tokens {
-            return Err(Error::InsufficientDTokenBalance);
-        }
-
         // Check that we're not trying to burn more dTokens than the user has in CDP
         let cur_d_tokens = cdp.d_tokens;
         let d_tokens_to_burn = if d_tokens > cur_d_tokens {
             // If trying to burn more than debt, only burn what's owed
+            // (a settled grace rebate can leave fewer dTokens than the caller saw)
             cur_d_tokens
         } else {
             d_tokens
         };
 
-        // Get current dTokenRate (12 decimals)
+        // Check borrower has enough dTokens
+        let borrower_balance = Storage::get_d_token_balance(env, borrower, asset);
+        if borrower_balance < d_tokens_to_burn {
+            return Err(Error::InsufficientDTokenBalance);
+        }
+
+        // Get dTokenRate (12 decimals)
         let d_token_rate = Storage::get_d_token_rate(env, asset);
 
         // Calculate amount to repay: dTokens × dTokenRate / SCALAR_12
@@ -213,6 +246,7 @@ impl Borrowing {
         }
         cdp.last_update = env.ledger().timestamp();
         Storage::set_cdp(env, borrower, &cdp);
+        Self::cap_interest_grace(env, borrower, asset, cdp.d_tokens)?;
 
         // Update dToken balance
         Storage::set_d_token_balance(env, borrower, asset, borrower_balance - d_tokens_to_burn);
@@ -226,8 +260,8 @@ impl Borrowing {
         Storage::set_pool_balance(env, asset, pool_balance + amount);
 
         // Transfer asset from borrower to pool
-        let token_address = Storage::get_token_contract(env, asset)
-            .ok_or(Error::TokenContractNotSet)?;
+        let token_address =
+            Storage::get_token_contract(env, asset).ok_or(Error::TokenContractNotSet)?;
         let token_client = TokenClient::new(env, &token_address);
         token_client.transfer(borrower, env.current_contract_address(), &amount);
 
@@ -237,6 +271,154 @@ impl Borrowing {
         Ok(amount)
     }
 
+    /// Repay debt by paying with a different asset, converted at the oracle cross-rate
+    ///
+    /// `pay_amount` of `pay_asset` is valued in `debt_asset` units using both
+    /// Reflector prices and the equivalent dTokens are burned. Payment beyond the
+    /// outstanding debt is not pulled. The received tokens are added to the
+    /// `pay_asset` pool balance.
+    ///
+    /// Fails with `OraclePriceNotFound` when the oracle has no price for
+    /// `pay_asset`, and with `DebtAssetAlreadySet` when `pay_asset` is the debt
+    /// asset itself (use `repay`).
+    ///
+    /// Returns the debt reduction in `debt_asset` units.
+    pub fn repay_with_asset(
+        env: &Env,
+        borrower: &Address,
+        debt_asset: &Symbol,
+        pay_asset: &Symbol,
+        pay_amount: i128,
+    ) -> Result<i128, Error> {
+        borrower.require_auth();
+
+        assert_with_error!(env, pay_amount > 0, Error::NotPositive);
+
+        if pay_asset == debt_asset {
+            return Err(Error::DebtAssetAlreadySet);
+        }
+        if !Oracles::has_crypto_price(env, pay_asset) {
+            return Err(Error::OraclePriceNotFound);
+        }
+
+        // Accrue interest and settle any interest-free window before repay
+        Interest::accrue_interest(env, debt_asset)?;
+        Self::settle_interest_grace(env, borrower, debt_asset)?;
+
+        // Get CDP
+        let mut cdp = Storage::get_cdp(env, borrower).ok_or(Error::DebtAssetNotSet)?;
+
+        // Check debt asset matches
+        if cdp.debt_asset.as_ref() != Some(debt_asset) {
+            return Err(Error::DebtAssetNotSet);
+        }
+
+        // Price both legs
+        let (pay_price, pay_price_decimals) =
+            Oracles::get_crypto_price_with_decimals(env, pay_asset)?;
+        let (debt_price, debt_price_decimals) =
+            Oracles::get_crypto_price_with_decimals(env, debt_asset)?;
+
+        let pay_token_address =
+            Storage::get_token_contract(env, pay_asset).ok_or(Error::TokenContractNotSet)?;
+        let pay_token_client = TokenClient::new(env, &pay_token_address);
+        let debt_token_address =
+            Storage::get_token_contract(env, debt_asset).ok_or(Error::TokenContractNotSet)?;
+        let pay_decimals = pay_token_client.decimals();
+        let debt_decimals = TokenClient::new(env, &debt_token_address).decimals();
+
+        // Convert pay_amount to debt asset units: USD value / debt price, then rescale decimals
+        let pay_value = Oracles::calculate_usd_value(
+            env,
+            pay_amount,
+            pay_price,
+            pay_decimals,
+            pay_price_decimals,
+        )?;
+        let debt_price_scalar = 10i128
+            .checked_pow(debt_price_decimals)
+            .ok_or(Error::ArithmeticError)?;
+        let debt_equivalent = Oracles::rescale(
+            pay_value
+                .checked_mul(debt_price_scalar)
+                .ok_or(Error::ArithmeticError)?
+                .checked_div(debt_price)
+                .ok_or(Error::ArithmeticError)?,
+            USD_DECIMALS,
+            debt_decimals,
+        )?;
+
+        // Check borrower has dTokens to burn
+        let borrower_balance = Storage::get_d_token_balance(env, borrower, debt_asset);
+        let d_token_rate = Storage::get_d_token_rate(env, debt_asset);
+
+        // Round down: favors the protocol (burns fewer dTokens)
+        let d_tokens = types::rounding::to_d_token_down(debt_equivalent, d_token_rate)?;
+        let d_tokens_to_burn = d_tokens.min(cdp.d_tokens).min(borrower_balance);
+        assert_with_error!(env, d_tokens_to_burn > 0, Error::NotPositive);
+
+        // Only pull the share of the payment needed when capped at the outstanding debt
+        let pay_used = if d_tokens_to_burn < d_tokens {
+            pay_amount
+                .checked_mul(d_tokens_to_burn)
+                .ok_or(Error::ArithmeticError)?
+                .checked_add(d_tokens - 1)
+                .ok_or(Error::ArithmeticError)?
+                .checked_div(d_tokens)
+                .ok_or(Error::ArithmeticError)?
+        } else {
+            pay_amount
+        };
+
+        // Calculate debt reduction: dTokens × dTokenRate / SCALAR_12
+        let debt_amount = d_tokens_to_burn
+            .checked_mul(d_token_rate)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_12)
+            .ok_or(Error::ArithmeticError)?;
+
+        // Update CDP
+        cdp.d_tokens -= d_tokens_to_burn;
+        if cdp.d_tokens == 0 {
+            cdp.debt_asset = None;
+        }
+        cdp.last_update = env.ledger().timestamp();
+        Storage::set_cdp(env, borrower, &cdp);
+        Self::cap_interest_grace(env, borrower, debt_asset, cdp.d_tokens)?;
+
+        // Update dToken balance
+        Storage::set_d_token_balance(
+            env,
+            borrower,
+            debt_asset,
+            borrower_balance - d_tokens_to_burn,
+        );
+
+        // Update dToken supply
+        let current_supply = Storage::get_d_token_supply(env, debt_asset);
+        Storage::set_d_token_supply(env, debt_asset, current_supply - d_tokens_to_burn);
+
+        // Payment joins the pay_asset pool
+        let pool_balance = Storage::get_pool_balance(env, pay_asset);
+        Storage::set_pool_balance(env, pay_asset, pool_balance + pay_used);
+
+        // Transfer pay asset from borrower to pool
+        pay_token_client.transfer(borrower, env.current_contract_address(), &pay_used);
+
+        // Emit event
+        Events::repay_with_asset(
+            env,
+            borrower,
+            debt_asset,
+            pay_asset,
+            pay_used,
+            debt_amount,
+            d_tokens_to_burn,
+        );
+
+        Ok(debt_amount)
+    }
+
     /// Calculate borrow limit for a borrower
     pub fn calculate_borrow_limit(env: &Env, borrower: &Address) -> Result<i128, Error> {
         // Get all collateral
@@ -252,20 +434,8 @@ impl Borrowing {
                 continue;
             }
 
-            // Get RWA token price (includes price decimals from oracle)
-            let (rwa_price, price_decimals) = Oracles::get_rwa_price_with_decimals(env, &rwa_token)?;
-            // Get token decimals from RWA token contract
-            let rwa_token_client = TokenClient::new(env, &rwa_token);
-            let rwa_decimals = rwa_token_client.decimals();
-
             // Calculate collateral value in USD
-            let collateral_value = Oracles::calculate_usd_value(
-                env,
-                collateral_amount,
-                rwa_price,
-                rwa_decimals,
-                price_decimals,
-            )?;
+            let collateral_value = Oracles::rwa_usd_value(env, &rwa_token, collateral_amount)?;
 
             // Get collateral factor (7 decimals)
             let collateral_factor = Admin::get_collateral_factor(env, &rwa_token);
@@ -288,28 +458,15 @@ impl Borrowing {
             if let Some(debt_asset) = &cdp.debt_asset {
                 if cdp.d_tokens > 0 {
                     let d_token_rate = Storage::get_d_token_rate(env, debt_asset);
-                    let debt_amount = cdp.d_tokens
+                    let debt_amount = cdp
+                        .d_tokens
                         .checked_mul(d_token_rate)
                         .ok_or(Error::ArithmeticError)?
                         .checked_div(SCALAR_12)
                         .ok_or(Error::ArithmeticError)?;
 
-                    // Get price of debt asset (includes price decimals from oracle)
-                    let (debt_price, price_decimals) = Oracles::get_crypto_price_with_decimals(env, debt_asset)?;
-                    // Get asset decimals from token contract
-                    let token_address = Storage::get_token_contract(env, debt_asset)
-                        .ok_or(Error::TokenContractNotSet)?;
-                    let token_client = TokenClient::new(env, &token_address);
-                    let asset_decimals = token_client.decimals();
-
                     // Calculate debt value in USD
-                    Oracles::calculate_usd_value(
-                        env,
-                        debt_amount,
-                        debt_price,
-                        asset_decimals,
-                        price_decimals,
-                    )?
+                    Oracles::crypto_usd_value(env, debt_asset, debt_amount)?
                 } else {
                     0
                 }
@@ -333,6 +490,205 @@ impl Borrowing {
         Storage::get_d_token_balance(env, borrower, asset)
     }
 
+    /// Open an interest-free window on the dTokens minted by a borrow
+    ///
+    /// The covered dTokens are counted in the reserve's `grace_d_supply`, which
+    /// keeps them out of utilization and the backstop take while the window is
+    /// open, so the interest later rebated on them was never credited to lenders
+    /// or the backstop.
+    fn open_interest_grace(
+        env: &Env,
+        borrower: &Address,
+        asset: &Symbol,
+        d_tokens: i128,
+        d_token_rate: i128,
+        grace_seconds: u64,
+    ) -> Result<(), Error> {
+        let now = env.ledger().timestamp();
+        let mut graces = Storage::get_interest_graces(env, borrower);
+        graces.push_back(InterestGrace {
+            ends_at: now.saturating_add(grace_seconds),
+            settled_at: now,
+            d_token_rate,
+            d_tokens,
+        });
+        Storage::set_interest_graces(env, borrower, &graces);
+
+        let mut reserve = Storage::get_reserve_data(env, asset);
+        reserve.grace_d_supply = reserve
+            .grace_d_supply
+            .checked_add(d_tokens)
+            .ok_or(Error::ArithmeticError)?;
+        Storage::set_reserve_data(env, asset, &reserve);
+
+        Ok(())
+    }
+
+    /// Settle a borrower's interest-free windows up to now
+    ///
+    /// Interest accrued inside each window is rebated by burning the matching
+    /// dTokens; windows that have closed are dropped and their remaining dTokens
+    /// start accruing for the reserve. Settles up to the reserve's last accrual,
+    /// where its dToken rate is exact. Called before every operation that values
+    /// the debt, so debt is always valued at the pool's dToken rate.
+    pub fn settle_interest_grace(
+        env: &Env,
+        borrower: &Address,
+        asset: &Symbol,
+    ) -> Result<(), Error> {
+        let graces = Storage::get_interest_graces(env, borrower);
+        if graces.is_empty() {
+            return Ok(());
+        }
+        let Some(mut cdp) = Storage::get_cdp(env, borrower) else {
+            return Ok(());
+        };
+        if cdp.debt_asset.as_ref() != Some(asset) {
+            return Ok(());
+        }
+
+        let mut reserve = Storage::get_reserve_data(env, asset);
+        let now = reserve.last_time;
+        let d_token_rate = reserve.d_rate;
+
+        let mut rebate = 0;
+        let mut released = 0;
+        let mut open = Vec::new(env);
+        for mut grace in graces.iter() {
+            let grace_rebate = Self::interest_grace_rebate(&grace, d_token_rate, now)?;
+            grace.d_tokens -= grace_rebate;
+            rebate += grace_rebate;
+            if now > grace.settled_at {
+                grace.settled_at = now;
+                grace.d_token_rate = d_token_rate;
+            }
+
+            if grace.settled_at >= grace.ends_at || grace.d_tokens == 0 {
+                released += grace.d_tokens;
+            } else {
+                open.push_back(grace);
+            }
+        }
+        Storage::set_interest_graces(env, borrower, &open);
+
+        if rebate > 0 {
+            cdp.d_tokens -= rebate;
+            if cdp.d_tokens == 0 {
+                cdp.debt_asset = None;
+            }
+            cdp.last_update = env.ledger().timestamp();
+            Storage::set_cdp(env, borrower, &cdp);
+
+            let balance = Storage::get_d_token_balance(env, borrower, asset);
+            Storage::set_d_token_balance(env, borrower, asset, balance - rebate);
+        }
+
+        // Rebated dTokens never accrued for the reserve, so burning them leaves
+        // lenders and the backstop untouched
+        if rebate + released > 0 {
+            reserve.d_supply -= rebate;
+            reserve.grace_d_supply -= rebate + released;
+            Storage::set_reserve_data(env, asset, &reserve);
+        }
+
+        Ok(())
+    }
+
+    /// Shrink the dTokens covered by a borrower's interest-free windows to the debt still owed
+    ///
+    /// Called after any dToken burn. Burns consume uncovered debt first, then the
+    /// oldest windows, so repaid debt is never rebated.
+    pub fn cap_interest_grace(
+        env: &Env,
+        borrower: &Address,
+        asset: &Symbol,
+        d_tokens: i128,
+    ) -> Result<(), Error> {
+        let graces = Storage::get_interest_graces(env, borrower);
+        let covered = graces.iter().map(|grace| grace.d_tokens).sum::<i128>();
+        let trimmed = covered - d_tokens;
+        if trimmed <= 0 {
+            return Ok(());
+        }
+
+        let mut excess = trimmed;
+        let mut open = Vec::new(env);
+        for mut grace in graces.iter() {
+            let take = excess.min(grace.d_tokens);
+            grace.d_tokens -= take;
+            excess -= take;
+            if grace.d_tokens > 0 {
+                open.push_back(grace);
+            }
+        }
+        Storage::set_interest_graces(env, borrower, &open);
+
+        let mut reserve = Storage::get_reserve_data(env, asset);
+        reserve.grace_d_supply = reserve
+            .grace_d_supply
+            .checked_sub(trimmed)
+            .ok_or(Error::ArithmeticError)?;
+        Storage::set_reserve_data(env, asset, &reserve);
+
+        Ok(())
+    }
+
+    /// dTokens to rebate for the open part of an interest-free window, from its
+    /// last settlement up to `now` (when the pool's rate was `d_token_rate`)
+    ///
+    /// Rate growth since the last settlement is pro-rated to the seconds that fell
+    /// inside the window and applied to the covered dTokens.
+    fn interest_grace_rebate(
+        grace: &InterestGrace,
+        d_token_rate: i128,
+        now: u64,
+    ) -> Result<i128, Error> {
+        if grace.settled_at >= grace.ends_at || now <= grace.settled_at || grace.d_tokens == 0 {
+            return Ok(0);
+        }
+
+        let covered = (now.min(grace.ends_at) - grace.settled_at) as i128;
+        let elapsed = (now - grace.settled_at) as i128;
+        let rate_growth = (d_token_rate - grace.d_token_rate)
+            .max(0)
+            .checked_mul(covered)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(elapsed)
+            .ok_or(Error::ArithmeticError)?;
+        let interest = grace
+            .d_tokens
+            .checked_mul(rate_growth)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_12)
+            .ok_or(Error::ArithmeticError)?;
+
+        // Round up: no interest inside the window is ever charged
+        Ok(types::rounding::to_d_token_up(interest, d_token_rate)?.min(grace.d_tokens))
+    }
+
+    /// Get a borrower's outstanding debt in the debt asset, net of any unsettled grace rebate
+    pub fn get_debt_amount(env: &Env, borrower: &Address) -> Result<i128, Error> {
+        let Some(cdp) = Storage::get_cdp(env, borrower) else {
+            return Ok(0);
+        };
+        let Some(debt_asset) = cdp.debt_asset else {
+            return Ok(0);
+        };
+
+        let reserve = Storage::get_reserve_data(env, &debt_asset);
+        let d_token_rate = reserve.d_rate;
+        let mut rebate = 0;
+        for grace in Storage::get_interest_graces(env, borrower).iter() {
+            rebate += Self::interest_grace_rebate(&grace, d_token_rate, reserve.last_time)?;
+        }
+
+        (cdp.d_tokens - rebate)
+            .checked_mul(d_token_rate)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_12)
+            .ok_or(Error::ArithmeticError)
+    }
+
     /// Get dTokenRate for an asset (12 decimals)
     pub fn get_d_token_rate(env: &Env, asset: &Symbol) -> i128 {
         Storage::get_d_token_rate(env, asset)
@@ -1,4 +1,4 @@
-use soroban_sdk::{assert_with_error, Address, Env, token::TokenClient};
+use soroban_sdk::{Address, Env, assert_with_error, token::TokenClient};
 
 use crate::admin::Admin;
 use crate::common::error::Error;
@@ -21,6 +21,38 @@ impl Collateral {
     ) -> Result<(), Error> {
         borrower.require_auth();
 
+        Self::credit_collateral(env, borrower, borrower, rwa_token, amount)
+    }
+
+    /// Add collateral to a borrower's CDP on their behalf from a trusted depositor
+    ///
+    /// The depositor (e.g. the perps contract posting a close payout) must be
+    /// allowed with `set_collateral_depositor`; the tokens are pulled from the
+    /// depositor and credited to `borrower` without the borrower's authorization.
+    pub fn deposit_collateral_for(
+        env: &Env,
+        depositor: &Address,
+        borrower: &Address,
+        rwa_token: &Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+
+        if !Admin::get_collateral_depositor(env, depositor) {
+            return Err(Error::NotAuthorized);
+        }
+
+        Self::credit_collateral(env, depositor, borrower, rwa_token, amount)
+    }
+
+    /// Pull `amount` of `rwa_token` from `from` and credit it to the borrower's CDP
+    fn credit_collateral(
+        env: &Env,
+        from: &Address,
+        borrower: &Address,
+        rwa_token: &Address,
+        amount: i128,
+    ) -> Result<(), Error> {
         assert_with_error!(env, amount > 0, Error::NotPositive);
 
         // Verify collateral factor is set for this RWA token
@@ -29,29 +61,29 @@ impl Collateral {
             return Err(Error::CollateralNotFound);
         }
 
-        // Transfer RWA tokens from borrower to contract
-        // Since borrower is already authenticated (via require_auth), we can use transfer directly
+        // Transfer RWA tokens from the payer to contract
+        // The payer is already authenticated by the caller, so we can use transfer directly
         let token_client = TokenClient::new(env, rwa_token);
         let contract_address = env.current_contract_address();
-        token_client.transfer(borrower, &contract_address, &amount);
+        token_client.transfer(from, &contract_address, &amount);
 
         // Update collateral balance
         let current_collateral = Storage::get_collateral(env, borrower, rwa_token);
         Storage::set_collateral(env, borrower, rwa_token, current_collateral + amount);
 
         // Update CDP
-        let mut cdp = Storage::get_cdp(env, borrower).unwrap_or_else(|| {
-            crate::common::types::CDP {
+        let mut cdp =
+            Storage::get_cdp(env, borrower).unwrap_or_else(|| crate::common::types::CDP {
                 collateral: soroban_sdk::Map::new(env),
                 debt_asset: None,
                 d_tokens: 0,
                 created_at: env.ledger().timestamp(),
                 last_update: env.ledger().timestamp(),
-            }
-        });
+            });
 
         // Update collateral in CDP
-        cdp.collateral.set(rwa_token.clone(), current_collateral + amount);
+        cdp.collateral
+            .set(rwa_token.clone(), current_collateral + amount);
         cdp.last_update = env.ledger().timestamp();
         Storage::set_cdp(env, borrower, &cdp);
 
@@ -78,71 +110,73 @@ impl Collateral {
             return Err(Error::InsufficientCollateral);
         }
 
+        // Value any debt net of any interest-free window
+        if let Some(debt_asset) = Storage::get_cdp(env, borrower).and_then(|cdp| cdp.debt_asset) {
+            Borrowing::settle_interest_grace(env, borrower, &debt_asset)?;
+        }
+
         // Check borrow limit after removal
         // If borrower has debt, verify they remain properly collateralized
         let cdp = Storage::get_cdp(env, borrower);
         if let Some(cdp) = &cdp
-            && cdp.d_tokens > 0 {
-                // Calculate borrow limit with reduced collateral
-                let new_collateral = current_collateral - amount;
-                Storage::set_collateral(env, borrower, rwa_token, new_collateral);
-                
-                // Temporarily update CDP to calculate new borrow limit
-                let mut temp_cdp = cdp.clone();
-                temp_cdp.collateral.set(rwa_token.clone(), new_collateral);
-                Storage::set_cdp(env, borrower, &temp_cdp);
-                
-                // Calculate borrow limit with new collateral
-                let borrow_limit = Borrowing::calculate_borrow_limit(env, borrower)?;
-                
-                // Get current debt value
-                if let Some(debt_asset) = &cdp.debt_asset {
-                    let d_token_rate = Storage::get_d_token_rate(env, debt_asset);
-                    let debt_amount = cdp.d_tokens
-                        .checked_mul(d_token_rate)
-                        .ok_or(Error::ArithmeticError)?
-                        .checked_div(SCALAR_12)
-                        .ok_or(Error::ArithmeticError)?;
-                    
-                    // Get price of debt asset
-                    let (debt_price, debt_decimals) = Oracles::get_crypto_price_with_decimals(env, debt_asset)?;
-                    let price_decimals = 7;
-                    let current_debt_value = Oracles::calculate_usd_value(
-                        env,
-                        debt_amount,
-                        debt_price,
-                        debt_decimals,
-                        price_decimals,
+            && cdp.d_tokens > 0
+        {
+            // Calculate borrow limit with reduced collateral
+            let new_collateral = current_collateral - amount;
+            Storage::set_collateral(env, borrower, rwa_token, new_collateral);
+
+            // Temporarily update CDP to calculate new borrow limit
+            let mut temp_cdp = cdp.clone();
+            temp_cdp.collateral.set(rwa_token.clone(), new_collateral);
+            Storage::set_cdp(env, borrower, &temp_cdp);
+
+            // Calculate borrow limit with new collateral
+            let borrow_limit = Borrowing::calculate_borrow_limit(env, borrower)?;
+
+            // Get current debt value
+            if let Some(debt_asset) = &cdp.debt_asset {
+                let d_token_rate = Storage::get_d_token_rate(env, debt_asset);
+                let debt_amount = cdp
+                    .d_tokens
+                    .checked_mul(d_token_rate)
+                    .ok_or(Error::ArithmeticError)?
+                    .checked_div(SCALAR_12)
+                    .ok_or(Error::ArithmeticError)?;
+
+                let current_debt_value = Oracles::crypto_usd_value(env, debt_asset, debt_amount)?;
+
+                // Restore original CDP
+                Storage::set_cdp(env, borrower, cdp);
+                Storage::set_collateral(env, borrower, rwa_token, current_collateral);
+
+                // Check if removal would make borrower undercollateralized
+                if current_debt_value > borrow_limit {
+                    return Err(Error::InsufficientBorrowLimit);
+                }
+
+                // Verify health factor remains above minimum threshold after removal (7 decimals)
+                // This ensures the borrower maintains a safety margin above liquidation threshold
+                let health_factor =
+                    crate::operations::liquidations::Liquidations::calculate_health_factor(
+                        env, borrower,
                     )?;
-                    
-                    // Restore original CDP
-                    Storage::set_cdp(env, borrower, cdp);
-                    Storage::set_collateral(env, borrower, rwa_token, current_collateral);
-                    
-                    // Check if removal would make borrower undercollateralized
-                    if current_debt_value > borrow_limit {
-                        return Err(Error::InsufficientBorrowLimit);
-                    }
-
-                    // Verify health factor remains above minimum threshold after removal (7 decimals)
-                    // This ensures the borrower maintains a safety margin above liquidation threshold
-                    let health_factor = crate::operations::liquidations::Liquidations::calculate_health_factor(env, borrower)?;
-                    if (health_factor as i128) < MIN_HEALTH_FACTOR {
-                        return Err(Error::HealthFactorTooLow);
-                    }
-                } else {
-                    // Restore original CDP
-                    Storage::set_cdp(env, borrower, cdp);
-                    Storage::set_collateral(env, borrower, rwa_token, current_collateral);
+                if (health_factor as i128) < MIN_HEALTH_FACTOR {
+                    return Err(Error::HealthFactorTooLow);
                 }
+            } else {
+                // Restore original CDP
+                Storage::set_cdp(env, borrower, cdp);
+                Storage::set_collateral(env, borrower, rwa_token, current_collateral);
             }
+        }
 
         // Update collateral balance
         Storage::set_collateral(env, borrower, rwa_token, current_collateral - amount);
 
         // Update CDP
         if let Some(mut cdp) = Storage::get_cdp(env, borrower) {
-            cdp.collateral.set(rwa_token.clone(), current_collateral - amount);
+            cdp.collateral
+                .set(rwa_token.clone(), current_collateral - amount);
             cdp.last_update = env.ledger().timestamp();
             Storage::set_cdp(env, borrower, &cdp);
         }
@@ -157,6 +191,43 @@ impl Collateral {
         Ok(())
     }
 
+    /// Flag an RWA token's collateral as unusable for new borrows while its price is stale
+    /// Callable by anyone (keepers). The flag holds until the oracle publishes a price
+    /// newer than the stale one. Returns whether the token was flagged
+    pub fn flag_stale_collateral(env: &Env, rwa_token: &Address) -> Result<bool, Error> {
+        let max_age = Admin::get_max_price_age(env, rwa_token);
+        if max_age == 0 {
+            return Ok(false);
+        }
+
+        let price_timestamp = Oracles::get_rwa_price(env, rwa_token)?.timestamp;
+        if price_timestamp.saturating_add(max_age) >= env.ledger().timestamp() {
+            return Ok(false);
+        }
+
+        let mut storage = Storage::get(env);
+        storage
+            .stale_collateral
+            .set(rwa_token.clone(), price_timestamp);
+        Storage::set(env, &storage);
+
+        Events::collateral_stale(env, rwa_token, price_timestamp);
+
+        Ok(true)
+    }
+
+    /// Whether an RWA token is flagged stale and still awaiting a fresh price
+    pub fn is_collateral_stale(env: &Env, rwa_token: &Address) -> bool {
+        let storage = Storage::get(env);
+        match storage.stale_collateral.get(rwa_token.clone()) {
+            Some(flagged_at) => match Oracles::get_rwa_price(env, rwa_token) {
+                Ok(price_data) => price_data.timestamp <= flagged_at,
+                Err(_) => true,
+            },
+            None => false,
+        }
+    }
+
     /// Get collateral amount for a borrower and RWA token
     pub fn get_collateral(env: &Env, borrower: &Address, rwa_token: &Address) -> i128 {
         Storage::get_collateral(env, borrower, rwa_token)
@@ -171,4 +242,3 @@ impl Collateral {
             .unwrap_or(soroban_sdk::Map::new(env))
     }
 }
-
@@ -3,7 +3,9 @@ use soroban_sdk::{Env, Symbol};
 use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::common::storage::{PoolStorage, Storage};
-use crate::common::types::{InterestRateParams, ReserveData, SCALAR_7, SCALAR_12, SECONDS_PER_YEAR};
+use crate::common::types::{
+    InterestRateParams, LN_2, ReserveData, SCALAR_7, SCALAR_12, SECONDS_PER_YEAR,
+};
 
 /// Interest rate calculations and accrual
 ///
@@ -57,11 +59,18 @@ impl Interest {
             return Ok(());
         }
 
-        // Calculate accrual and update reserve
+        // Calculate the (optionally smoothed) rate, then accrual
+        let interest_rate = Self::smooth_rate(
+            Self::calc_interest_rate(&params, utilization, reserve.ir_mod)?,
+            reserve.last_rate,
+            current_time - reserve.last_time,
+            storage.rate_half_life,
+        )?;
         let (accrual, new_ir_mod) = Self::calc_accrual(
             &params,
             utilization,
             reserve.ir_mod,
+            interest_rate,
             reserve.last_time,
             current_time,
         )?;
@@ -77,43 +86,31 @@ impl Interest {
             current_time,
         )?;
 
+        // Remember the effective rate for the next smoothing step
+        reserve.last_rate = interest_rate;
+
         // Save updated reserve
         storage.reserve_data.set(asset.clone(), reserve.clone());
         Storage::set(env, &storage);
 
         // Emit event
-        Events::interest_accrued(
-            env,
-            asset,
-            reserve.b_rate,
-            reserve.d_rate,
-            reserve.ir_mod,
-        );
+        Events::interest_accrued(env, asset, reserve.b_rate, reserve.d_rate, reserve.ir_mod);
 
         Ok(())
     }
 
-    /// Calculate accrual ratio and new interest rate modifier
-    /// Returns (accrual_12d, new_ir_mod_7d)
-    fn calc_accrual(
+    /// Calculate the target interest rate from utilization (7 decimals)
+    fn calc_interest_rate(
         params: &InterestRateParams,
-        cur_util: i128,  // 7 decimals
-        ir_mod: i128,    // 7 decimals
-        last_time: u64,
-        current_time: u64,
-    ) -> Result<(i128, i128), Error> {
-        let delta_time = current_time.saturating_sub(last_time);
-        if delta_time == 0 {
-            return Ok((SCALAR_12, ir_mod));
-        }
-
+        cur_util: i128, // 7 decimals
+        ir_mod: i128,   // 7 decimals
+    ) -> Result<i128, Error> {
         let target_util = params.target_util as i128;
         let max_util = params.max_util as i128;
         let r_base = params.r_base as i128;
         let r_one = params.r_one as i128;
         let r_two = params.r_two as i128;
         let r_three = params.r_three as i128;
-        let reactivity = params.reactivity as i128;
 
         // Calculate interest rate based on utilization segment
         let interest_rate = if cur_util <= target_util {
@@ -139,8 +136,12 @@ impl Interest {
             // Segment 2: target < util <= max (95%)
             // rate = ((util - target) / (max - target)) * R2 + R1 + R0
             // rate = rate * ir_mod / SCALAR_7
-            let util_diff = cur_util.checked_sub(target_util).ok_or(Error::ArithmeticError)?;
-            let range = max_util.checked_sub(target_util).ok_or(Error::ArithmeticError)?;
+            let util_diff = cur_util
+                .checked_sub(target_util)
+                .ok_or(Error::ArithmeticError)?;
+            let range = max_util
+                .checked_sub(target_util)
+                .ok_or(Error::ArithmeticError)?;
             let rate = if range > 0 {
                 util_diff
                     .checked_mul(r_two)
@@ -162,8 +163,12 @@ impl Interest {
             // Segment 3: util > max (95%)
             // rate = ((util - max) / (1 - max)) * R3 + R2 + R1 + R0
             // Note: No ir_mod multiplication in segment 3 (like Blend)
-            let util_diff = cur_util.checked_sub(max_util).ok_or(Error::ArithmeticError)?;
-            let range = SCALAR_7.checked_sub(max_util).ok_or(Error::ArithmeticError)?;
+            let util_diff = cur_util
+                .checked_sub(max_util)
+                .ok_or(Error::ArithmeticError)?;
+            let range = SCALAR_7
+                .checked_sub(max_util)
+                .ok_or(Error::ArithmeticError)?;
             if range > 0 {
                 util_diff
                     .checked_mul(r_three)
@@ -187,6 +192,76 @@ impl Interest {
             }
         };
 
+        Ok(interest_rate)
+    }
+
+    /// Move the effective rate toward the target rate over `elapsed` seconds (7 decimals)
+    /// rate = last_rate * w + target_rate * (1 - w), w = 0.5 ^ (elapsed / half_life)
+    /// No smoothing is applied when disabled or before the first accrual
+    fn smooth_rate(
+        target_rate: i128,
+        last_rate: i128,
+        elapsed: u64,
+        half_life: u64,
+    ) -> Result<i128, Error> {
+        if half_life == 0 || last_rate == 0 {
+            return Ok(target_rate);
+        }
+
+        let weight = Self::half_life_weight(elapsed, half_life)?;
+        let kept = last_rate
+            .checked_mul(weight)
+            .ok_or(Error::ArithmeticError)?;
+        let moved = target_rate
+            .checked_mul(SCALAR_7 - weight)
+            .ok_or(Error::ArithmeticError)?;
+
+        kept.checked_add(moved)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_7)
+            .ok_or(Error::ArithmeticError)
+    }
+
+    /// Weight kept after `elapsed` seconds of decay: 0.5 ^ (elapsed / half_life) (7 decimals)
+    /// Whole half-lives halve the weight exactly; the fractional part uses
+    /// e^(-ln2 * f) ~ 1 - y + y^2/2 - y^3/6 with y = ln2 * f
+    fn half_life_weight(elapsed: u64, half_life: u64) -> Result<i128, Error> {
+        let halvings = elapsed / half_life;
+        if halvings >= 24 {
+            // SCALAR_7 >> 24 == 0
+            return Ok(0);
+        }
+
+        let fraction = ((elapsed % half_life) as i128)
+            .checked_mul(SCALAR_7)
+            .ok_or(Error::ArithmeticError)?
+            / half_life as i128;
+        let y = fraction * LN_2 / SCALAR_7;
+        let y2 = y * y / SCALAR_7;
+        let y3 = y2 * y / SCALAR_7;
+        let decay = SCALAR_7 - y + y2 / 2 - y3 / 6;
+
+        Ok(decay >> halvings)
+    }
+
+    /// Calculate accrual ratio and new interest rate modifier
+    /// Returns (accrual_12d, new_ir_mod_7d)
+    fn calc_accrual(
+        params: &InterestRateParams,
+        cur_util: i128,      // 7 decimals
+        ir_mod: i128,        // 7 decimals
+        interest_rate: i128, // 7 decimals
+        last_time: u64,
+        current_time: u64,
+    ) -> Result<(i128, i128), Error> {
+        let delta_time = current_time.saturating_sub(last_time);
+        if delta_time == 0 {
+            return Ok((SCALAR_12, ir_mod));
+        }
+
+        let target_util = params.target_util as i128;
+        let reactivity = params.reactivity as i128;
+
         // Calculate accrual ratio (12 decimals)
         // accrual = SCALAR_12 + (interest_rate * delta_time * SCALAR_12) / (SECONDS_PER_YEAR * SCALAR_7)
         let time_weight_numerator = (delta_time as i128)
@@ -210,7 +285,9 @@ impl Interest {
         // Calculate new rate modifier
         // util_dif = cur_util - target_util
         // ir_mod_change = delta_time * util_dif * reactivity / SCALAR_7
-        let util_dif = cur_util.checked_sub(target_util).ok_or(Error::ArithmeticError)?;
+        let util_dif = cur_util
+            .checked_sub(target_util)
+            .ok_or(Error::ArithmeticError)?;
 
         let ir_mod_change = (delta_time as i128)
             .checked_mul(util_dif)
@@ -225,8 +302,8 @@ impl Interest {
             .ok_or(Error::ArithmeticError)?;
 
         // Bound ir_mod: min = 0.1 (SCALAR_7 / 10), max = 10 (SCALAR_7 * 10)
-        let min_ir_mod = SCALAR_7 / 10;  // 0.1
-        let max_ir_mod = SCALAR_7 * 10;  // 10.0
+        let min_ir_mod = SCALAR_7 / 10; // 0.1
+        let max_ir_mod = SCALAR_7 * 10; // 10.0
         let new_ir_mod = new_ir_mod_raw.clamp(min_ir_mod, max_ir_mod);
 
         Ok((accrual, new_ir_mod))
@@ -238,8 +315,8 @@ impl Interest {
         reserve: &mut ReserveData,
         storage: &PoolStorage,
         _asset: &Symbol,
-        accrual: i128,  // 12 decimals
-        new_ir_mod: i128,  // 7 decimals
+        accrual: i128,    // 12 decimals
+        new_ir_mod: i128, // 7 decimals
         current_time: u64,
     ) -> Result<(), Error> {
         // Save old d_rate before updating
@@ -253,14 +330,20 @@ impl Interest {
             .ok_or(Error::ArithmeticError)?;
 
         // Calculate backstop take from interest earned
+        // dTokens inside an interest-free window earn nothing
+        let accruing_d_supply = reserve
+            .d_supply
+            .checked_sub(reserve.grace_d_supply)
+            .ok_or(Error::ArithmeticError)?;
         let backstop_take_rate = storage.backstop_take_rate as i128;
-        if backstop_take_rate > 0 && reserve.d_supply > 0 {
-            // Interest earned = d_supply * (new_d_rate - old_d_rate) / SCALAR_12
-            let rate_increase = reserve.d_rate
+        if backstop_take_rate > 0 && accruing_d_supply > 0 {
+            // Interest earned = (d_supply - grace_d_supply) * (new_d_rate - old_d_rate) / SCALAR_12
+            let rate_increase = reserve
+                .d_rate
                 .checked_sub(old_d_rate)
                 .ok_or(Error::ArithmeticError)?;
 
-            let interest_earned = reserve.d_supply
+            let interest_earned = accruing_d_supply
                 .checked_mul(rate_increase)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(SCALAR_12)
@@ -353,9 +436,12 @@ impl Interest {
             return Ok(0);
         }
 
-        // Total liabilities = d_supply * d_rate / SCALAR_12
+        // Total liabilities = (d_supply - grace_d_supply) * d_rate / SCALAR_12
+        // Debt inside an interest-free window does not drive the rate
         let total_liabilities = reserve
             .d_supply
+            .checked_sub(reserve.grace_d_supply)
+            .ok_or(Error::ArithmeticError)?
             .checked_mul(reserve.d_rate)
             .ok_or(Error::ArithmeticError)?
             .checked_div(SCALAR_12)
@@ -391,13 +477,22 @@ impl Interest {
 
         let utilization = Self::calculate_utilization_internal(&reserve)?;
 
+        // Rate that would apply if accrued now
+        let interest_rate = Self::smooth_rate(
+            Self::calc_interest_rate(&params, utilization, reserve.ir_mod)?,
+            reserve.last_rate,
+            env.ledger().timestamp().saturating_sub(reserve.last_time),
+            storage.rate_half_life,
+        )?;
+
         // Calculate rate without accruing
         let (accrual, _) = Self::calc_accrual(
             &params,
             utilization,
             reserve.ir_mod,
+            interest_rate,
             reserve.last_time,
-            reserve.last_time + 1,  // Simulate 1 second
+            reserve.last_time + 1, // Simulate 1 second
         )?;
 
         // Convert accrual to annual rate (7 decimals)
@@ -420,13 +515,13 @@ impl Interest {
     /// Default interest rate parameters (Blend-style)
     pub fn default_params() -> InterestRateParams {
         InterestRateParams {
-            target_util: 7_500_000,    // 75%
-            max_util: 9_500_000,       // 95%
-            r_base: 100_000,           // 1%
-            r_one: 500_000,            // 5%
-            r_two: 5_000_000,          // 50%
-            r_three: 15_000_000,       // 150%
-            reactivity: 200,           // 0.00002
+            target_util: 7_500_000, // 75%
+            max_util: 9_500_000,    // 95%
+            r_base: 100_000,        // 1%
+            r_one: 500_000,         // 5%
+            r_two: 5_000_000,       // 50%
+            r_three: 15_000_000,    // 150%
+            reactivity: 200,        // 0.00002
         }
     }
 }
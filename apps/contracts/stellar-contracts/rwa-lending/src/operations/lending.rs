@@ -1,4 +1,4 @@
-use soroban_sdk::{assert_with_error, Address, Env, Symbol, token::TokenClient};
+use soroban_sdk::{Address, Env, Symbol, assert_with_error, token::TokenClient};
 
 use crate::admin::Admin;
 use crate::common::error::Error;
@@ -38,9 +38,17 @@ impl Lending {
         // This favors the protocol by minting fewer bTokens
         let b_tokens = types::rounding::to_b_token_down(amount, b_token_rate)?;
 
+        // A new supplier needs a free holder slot in this reserve
+        if b_tokens > 0 && Storage::get_b_token_balance(env, lender, asset) == 0 {
+            let max_suppliers = Admin::get_max_suppliers(env, asset);
+            if max_suppliers > 0 && Storage::get_supplier_count(env, asset) >= max_suppliers {
+                return Err(Error::HolderCapReached);
+            }
+        }
+
         // Transfer asset from lender to pool
-        let token_address = Storage::get_token_contract(env, asset)
-            .ok_or(Error::TokenContractNotSet)?;
+        let token_address =
+            Storage::get_token_contract(env, asset).ok_or(Error::TokenContractNotSet)?;
         let token_client = TokenClient::new(env, &token_address);
         token_client.transfer(lender, env.current_contract_address(), &amount);
 
@@ -127,8 +135,8 @@ impl Lending {
         }
 
         // Transfer asset from pool to lender
-        let token_address = Storage::get_token_contract(env, asset)
-            .ok_or(Error::TokenContractNotSet)?;
+        let token_address =
+            Storage::get_token_contract(env, asset).ok_or(Error::TokenContractNotSet)?;
         let token_client = TokenClient::new(env, &token_address);
         token_client.transfer(&env.current_contract_address(), lender, &amount);
 
@@ -153,4 +161,3 @@ impl Lending {
         Storage::get_b_token_supply(env, asset)
     }
 }
-
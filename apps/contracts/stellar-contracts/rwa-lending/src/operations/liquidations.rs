@@ -2,13 +2,28 @@ use soroban_sdk::{Address, Env, Map, Symbol, token::TokenClient};
 
 use crate::common::error::Error;
 use crate::common::storage::Storage;
-use crate::common::types::{AuctionData, AuctionType, AUCTION_DURATION_BLOCKS, MAX_HEALTH_FACTOR, SCALAR_7, SCALAR_12};
+use crate::common::types::{
+    AUCTION_DURATION_BLOCKS, AuctionData, AuctionType, MAX_HEALTH_FACTOR, SCALAR_7, SCALAR_12,
+};
+use crate::operations::borrowing::Borrowing;
 use crate::operations::collateral::Collateral;
 use crate::operations::oracles::Oracles;
 
 /// Liquidation functions AuctionStatus
 pub struct Liquidations;
 
+/// Lot/bid amounts for filling (part of) a liquidation auction
+struct PartialFill {
+    rwa_token: Address,
+    debt_token_address: Address,
+    lot_total: i128,
+    bid_total: i128,
+    lot_filled: i128,
+    bid_filled: i128,
+    collateral_received: i128,
+    debt_to_pay: i128,
+}
+
 impl Liquidations {
     /// Initiate a liquidation auction for a borrower
     /// Returns the auction ID (u32)
@@ -19,9 +34,11 @@ impl Liquidations {
         debt_asset: &Symbol,
         liquidation_percent: u32,
     ) -> Result<u32, Error> {
+        // Value the debt net of any interest-free window
+        Borrowing::settle_interest_grace(env, borrower, debt_asset)?;
+
         // Get CDP
-        let cdp = Storage::get_cdp(env, borrower)
-            .ok_or(Error::CDPNotInsolvent)?;
+        let cdp = Storage::get_cdp(env, borrower).ok_or(Error::CDPNotInsolvent)?;
 
         // Check if borrower has debt in this asset
         if cdp.debt_asset.as_ref() != Some(debt_asset) {
@@ -45,7 +62,8 @@ impl Liquidations {
 
         // Get debt amount (using SCALAR_12 for dToken rate)
         let d_token_rate = Storage::get_d_token_rate(env, debt_asset);
-        let debt_amount = cdp.d_tokens
+        let debt_amount = cdp
+            .d_tokens
             .checked_mul(d_token_rate)
             .ok_or(Error::ArithmeticError)?
             .checked_div(SCALAR_12)
@@ -76,29 +94,14 @@ impl Liquidations {
             .ok_or(Error::ArithmeticError)?
             .checked_div(2)
             .ok_or(Error::ArithmeticError)?)
-            .checked_add(SCALAR_7)
-            .ok_or(Error::ArithmeticError)?;
+        .checked_add(SCALAR_7)
+        .ok_or(Error::ArithmeticError)?;
 
         // Get total collateral value for this RWA token
-        let (rwa_price, rwa_decimals) = Oracles::get_rwa_price_with_decimals(env, rwa_token)?;
-        let price_decimals = 7;
-        let total_collateral_value = Oracles::calculate_usd_value(
-            env,
-            collateral_amount,
-            rwa_price,
-            rwa_decimals,
-            price_decimals,
-        )?;
+        let total_collateral_value = Oracles::rwa_usd_value(env, rwa_token, collateral_amount)?;
 
         // Get total debt value
-        let (debt_price, debt_decimals) = Oracles::get_crypto_price_with_decimals(env, debt_asset)?;
-        let total_debt_value = Oracles::calculate_usd_value(
-            env,
-            debt_amount,
-            debt_price,
-            debt_decimals,
-            price_decimals,
-        )?;
+        let total_debt_value = Oracles::crypto_usd_value(env, debt_asset, debt_amount)?;
 
         // Calculate collateral percentage: C_p = (p * L_p * L_o) / C_o
         let collateral_percent = premium
@@ -125,8 +128,8 @@ impl Liquidations {
         let auction_id = Self::generate_auction_id(env);
 
         // Get token contract address for debt asset
-        let debt_token_address = Storage::get_token_contract(env, debt_asset)
-            .ok_or(Error::TokenContractNotSet)?;
+        let debt_token_address =
+            Storage::get_token_contract(env, debt_asset).ok_or(Error::TokenContractNotSet)?;
 
         // Create lot map (collateral - what liquidator receives)
         let mut lot = Map::new(env);
@@ -141,6 +144,7 @@ impl Liquidations {
             auction_type: AuctionType::UserLiquidation,
             user: borrower.clone(),
             bid,
+            initial_lot: lot.clone(),
             lot,
             block: env.ledger().sequence(),
         };
@@ -164,70 +168,50 @@ impl Liquidations {
         Ok(auction_id)
     }
 
-    /// Fill a liquidation auction
+    /// Fill a liquidation auction (fully or partially)
+    ///
+    /// # Arguments
+    /// * `env` - The environment
+    /// * `auction_id` - The auction to fill
+    /// * `liquidator` - The address filling the auction
+    /// * `fill_percent` - Percentage of the remaining lot/bid to fill (7 decimals, max SCALAR_7)
+    ///
+    /// Fills seizing more collateral value than `max_liq_seize_usd`
+    /// are scaled down to the cap, leaving the rest of the auction for later calls.
+    ///
+    /// Returns (collateral_received, debt_paid). The auction is removed only when fully filled.
     pub fn fill_auction(
         env: &Env,
         auction_id: u32,
         liquidator: &Address,
-    ) -> Result<(), Error> {
+        fill_percent: i128,
+    ) -> Result<(i128, i128), Error> {
         liquidator.require_auth();
 
-        let mut storage = Storage::get(env);
-        let auction = storage
+        let auction = Storage::get(env)
             .auction_data
             .get(auction_id)
             .ok_or(Error::AuctionNotFound)?;
 
-        // Verify it's a user liquidation auction
-        if auction.auction_type != AuctionType::UserLiquidation {
-            return Err(Error::AuctionNotActive);
-        }
-
-        // Calculate blocks elapsed
-        let blocks_elapsed = env.ledger().sequence() - auction.block;
-        let (lot_modifier, bid_modifier) = Self::calculate_auction_modifiers(blocks_elapsed);
-
-        // Get collateral info from lot map (first entry)
-        let lot_keys: soroban_sdk::Vec<Address> = auction.lot.keys();
-        if lot_keys.is_empty() {
-            return Err(Error::AuctionNotActive);
-        }
-        let rwa_token = lot_keys.get(0).ok_or(Error::AuctionNotActive)?;
-        let collateral_amount = auction.lot.get(rwa_token.clone()).unwrap_or(0);
-
-        // Get debt info from bid map (first entry)
-        let bid_keys: soroban_sdk::Vec<Address> = auction.bid.keys();
-        if bid_keys.is_empty() {
-            return Err(Error::AuctionNotActive);
-        }
-        let debt_token_address = bid_keys.get(0).ok_or(Error::AuctionNotActive)?;
-        let debt_amount = auction.bid.get(debt_token_address.clone()).unwrap_or(0);
-
-        // Calculate collateral to receive and debt to pay (modifiers use SCALAR_12)
-        let collateral_received = collateral_amount
-            .checked_mul(lot_modifier)
-            .ok_or(Error::ArithmeticError)?
-            .checked_div(SCALAR_12)
-            .ok_or(Error::ArithmeticError)?;
-
-        let debt_to_pay = debt_amount
-            .checked_mul(bid_modifier)
-            .ok_or(Error::ArithmeticError)?
-            .checked_div(SCALAR_12)
-            .ok_or(Error::ArithmeticError)?;
+        let fill = Self::calculate_partial_fill(env, &auction, fill_percent)?;
+        let collateral_received = fill.collateral_received;
+        let debt_to_pay = fill.debt_to_pay;
 
         // Transfer debt asset from liquidator to pool
-        let token_client = TokenClient::new(env, &debt_token_address);
+        let token_client = TokenClient::new(env, &fill.debt_token_address);
         token_client.transfer(liquidator, env.current_contract_address(), &debt_to_pay);
 
         // Transfer collateral from contract to liquidator
-        let rwa_token_client = TokenClient::new(env, &rwa_token);
-        rwa_token_client.transfer(&env.current_contract_address(), liquidator, &collateral_received);
+        let rwa_token_client = TokenClient::new(env, &fill.rwa_token);
+        rwa_token_client.transfer(
+            &env.current_contract_address(),
+            liquidator,
+            &collateral_received,
+        );
 
         // Update CDP
         let borrower = &auction.user;
-        let mut cdp = Storage::get_cdp(env, borrower)
-            .ok_or(Error::CDPNotInsolvent)?;
+        let mut cdp = Storage::get_cdp(env, borrower).ok_or(Error::CDPNotInsolvent)?;
 
         // Get debt asset symbol from CDP
         let debt_asset = cdp.debt_asset.clone().ok_or(Error::DebtAssetNotSet)?;
@@ -246,14 +230,25 @@ impl Liquidations {
         }
         cdp.last_update = env.ledger().timestamp();
         Storage::set_cdp(env, borrower, &cdp);
+        Borrowing::cap_interest_grace(env, borrower, &debt_asset, cdp.d_tokens)?;
 
         // Update collateral
-        let current_collateral = Storage::get_collateral(env, borrower, &rwa_token);
-        Storage::set_collateral(env, borrower, &rwa_token, current_collateral - collateral_received);
+        let current_collateral = Storage::get_collateral(env, borrower, &fill.rwa_token);
+        Storage::set_collateral(
+            env,
+            borrower,
+            &fill.rwa_token,
+            current_collateral - collateral_received,
+        );
 
         // Update dToken balance
         let current_balance = Storage::get_d_token_balance(env, borrower, &debt_asset);
-        Storage::set_d_token_balance(env, borrower, &debt_asset, current_balance - d_tokens_to_burn);
+        Storage::set_d_token_balance(
+            env,
+            borrower,
+            &debt_asset,
+            current_balance - d_tokens_to_burn,
+        );
 
         // Update pool balance
         let pool_balance = Storage::get_pool_balance(env, &debt_asset);
@@ -265,8 +260,23 @@ impl Liquidations {
             return Err(Error::HealthFactorTooHigh);
         }
 
-        // Remove auction (it's been filled)
-        storage.auction_data.remove(auction_id);
+        // Remove the auction once fully filled, otherwise keep the remainder
+        // (reload storage so the balance updates above are not overwritten)
+        let mut storage = Storage::get(env);
+        let remaining_lot = fill.lot_total - fill.lot_filled;
+        let remaining_bid = fill.bid_total - fill.bid_filled;
+        if remaining_lot <= 0 && remaining_bid <= 0 {
+            storage.auction_data.remove(auction_id);
+        } else {
+            let mut updated_auction = auction.clone();
+            updated_auction
+                .lot
+                .set(fill.rwa_token.clone(), remaining_lot);
+            updated_auction
+                .bid
+                .set(fill.debt_token_address.clone(), remaining_bid);
+            storage.auction_data.set(auction_id, updated_auction);
+        }
         Storage::set(env, &storage);
 
         // Emit event
@@ -278,16 +288,33 @@ impl Liquidations {
             debt_to_pay,
         );
 
-        Ok(())
+        Ok((collateral_received, debt_to_pay))
+    }
+
+    /// Simulate filling part of a liquidation auction at the current block
+    ///
+    /// Returns (collateral_received, debt_to_pay) for the given fill percent
+    /// (7 decimals, max SCALAR_7) without changing any state.
+    pub fn simulate_partial_fill(
+        env: &Env,
+        auction_id: u32,
+        fill_percent: i128,
+    ) -> Result<(i128, i128), Error> {
+        let auction = Storage::get(env)
+            .auction_data
+            .get(auction_id)
+            .ok_or(Error::AuctionNotFound)?;
+
+        let fill = Self::calculate_partial_fill(env, &auction, fill_percent)?;
+        Ok((fill.collateral_received, fill.debt_to_pay))
     }
 
     /// Calculate health factor for a borrower
-    /// Health Factor = (CollateralValue × CollateralFactor) / DebtValue
+    /// Health Factor = (CollateralValue × LiquidationThreshold) / DebtValue
     /// Returns health factor in 7 decimals (10_000_000 = 1.0)
     pub fn calculate_health_factor(env: &Env, borrower: &Address) -> Result<u32, Error> {
         // Get CDP
-        let cdp = Storage::get_cdp(env, borrower)
-            .ok_or(Error::CDPNotInsolvent)?;
+        let cdp = Storage::get_cdp(env, borrower).ok_or(Error::CDPNotInsolvent)?;
 
         // Calculate total collateral value
         let all_collateral = Collateral::get_all_collateral(env, borrower);
@@ -300,25 +327,16 @@ impl Liquidations {
                 continue;
             }
 
-            // Get RWA token price
-            let (rwa_price, rwa_decimals) = Oracles::get_rwa_price_with_decimals(env, &rwa_token)?;
-            let price_decimals = 7;
-
             // Calculate collateral value in USD
-            let collateral_value = Oracles::calculate_usd_value(
-                env,
-                collateral_amount,
-                rwa_price,
-                rwa_decimals,
-                price_decimals,
-            )?;
-
-            // Get collateral factor (7 decimals)
-            let collateral_factor = crate::admin::Admin::get_collateral_factor(env, &rwa_token);
-
-            // Add to total: CollateralValue × CollateralFactor / SCALAR_7
+            let collateral_value = Oracles::rwa_usd_value(env, &rwa_token, collateral_amount)?;
+
+            // Get liquidation threshold (7 decimals, >= collateral factor)
+            let liquidation_threshold =
+                crate::admin::Admin::get_liquidation_threshold(env, &rwa_token);
+
+            // Add to total: CollateralValue × LiquidationThreshold / SCALAR_7
             let factored_value = collateral_value
-                .checked_mul(collateral_factor as i128)
+                .checked_mul(liquidation_threshold as i128)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(SCALAR_7)
                 .ok_or(Error::ArithmeticError)?;
@@ -332,24 +350,15 @@ impl Liquidations {
         let total_debt_value = if let Some(debt_asset) = &cdp.debt_asset {
             if cdp.d_tokens > 0 {
                 let d_token_rate = Storage::get_d_token_rate(env, debt_asset);
-                let debt_amount = cdp.d_tokens
+                let debt_amount = cdp
+                    .d_tokens
                     .checked_mul(d_token_rate)
                     .ok_or(Error::ArithmeticError)?
                     .checked_div(SCALAR_12)
                     .ok_or(Error::ArithmeticError)?;
 
-                // Get price of debt asset
-                let (debt_price, debt_decimals) = Oracles::get_crypto_price_with_decimals(env, debt_asset)?;
-                let price_decimals = 7;
-
                 // Calculate debt value in USD
-                Oracles::calculate_usd_value(
-                    env,
-                    debt_amount,
-                    debt_price,
-                    debt_decimals,
-                    price_decimals,
-                )?
+                Oracles::crypto_usd_value(env, debt_asset, debt_amount)?
             } else {
                 0
             }
@@ -362,7 +371,7 @@ impl Liquidations {
             return Ok(u32::MAX);
         }
 
-        // Health Factor = (CollateralValue × CollateralFactor) / DebtValue
+        // Health Factor = (CollateralValue × LiquidationThreshold) / DebtValue
         // With 7 decimals: HF = (total_collateral_value * SCALAR_7) / total_debt_value
         let health_factor = total_collateral_value
             .checked_mul(SCALAR_7)
@@ -374,6 +383,126 @@ impl Liquidations {
         Ok(health_factor.min(u32::MAX as i128) as u32)
     }
 
+    /// Calculate the lot/bid amounts for filling part of a liquidation auction
+    fn calculate_partial_fill(
+        env: &Env,
+        auction: &AuctionData,
+        fill_percent: i128,
+    ) -> Result<PartialFill, Error> {
+        // Validate fill percentage
+        if fill_percent <= 0 || fill_percent > SCALAR_7 {
+            return Err(Error::InvalidFillPercent);
+        }
+
+        // Verify it's a user liquidation auction
+        if auction.auction_type != AuctionType::UserLiquidation {
+            return Err(Error::AuctionNotActive);
+        }
+
+        // Calculate blocks elapsed
+        let blocks_elapsed = env.ledger().sequence() - auction.block;
+        let (lot_modifier, bid_modifier) = Self::calculate_auction_modifiers(blocks_elapsed);
+
+        // Get collateral info from lot map (first entry)
+        let lot_keys: soroban_sdk::Vec<Address> = auction.lot.keys();
+        if lot_keys.is_empty() {
+            return Err(Error::AuctionNotActive);
+        }
+        let rwa_token = lot_keys.get(0).ok_or(Error::AuctionNotActive)?;
+        let lot_total = auction.lot.get(rwa_token.clone()).unwrap_or(0);
+
+        // Get debt info from bid map (first entry)
+        let bid_keys: soroban_sdk::Vec<Address> = auction.bid.keys();
+        if bid_keys.is_empty() {
+            return Err(Error::AuctionNotActive);
+        }
+        let debt_token_address = bid_keys.get(0).ok_or(Error::AuctionNotActive)?;
+        let bid_total = auction.bid.get(debt_token_address.clone()).unwrap_or(0);
+
+        // Portion of the remaining lot/bid being filled (a full fill takes everything, avoiding dust)
+        let (mut lot_filled, mut bid_filled) = if fill_percent == SCALAR_7 {
+            (lot_total, bid_total)
+        } else {
+            (
+                lot_total
+                    .checked_mul(fill_percent)
+                    .ok_or(Error::ArithmeticError)?
+                    .checked_div(SCALAR_7)
+                    .ok_or(Error::ArithmeticError)?,
+                bid_total
+                    .checked_mul(fill_percent)
+                    .ok_or(Error::ArithmeticError)?
+                    .checked_div(SCALAR_7)
+                    .ok_or(Error::ArithmeticError)?,
+            )
+        };
+
+        // Reject dust fills, measured against the original lot so repeated small
+        // fills cannot shrink the minimum; the final fill closing out the remainder
+        // is always allowed
+        if fill_percent < SCALAR_7 {
+            let min_lot = auction
+                .initial_lot
+                .get(rwa_token.clone())
+                .unwrap_or(lot_total)
+                .checked_mul(Storage::get(env).min_fill_percent as i128)
+                .ok_or(Error::ArithmeticError)?
+                .checked_div(SCALAR_7)
+                .ok_or(Error::ArithmeticError)?;
+            if lot_filled < min_lot {
+                return Err(Error::InvalidFillPercent);
+            }
+        }
+
+        // Calculate collateral to receive and debt to pay (modifiers use SCALAR_12)
+        let mut collateral_received = lot_filled
+            .checked_mul(lot_modifier)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_12)
+            .ok_or(Error::ArithmeticError)?;
+
+        // Scale the fill down so one call cannot seize more than the configured value
+        let max_lot_value = Storage::get(env).max_liq_seize_usd;
+        if max_lot_value > 0 && collateral_received > 0 {
+            let lot_value = Oracles::rwa_usd_value(env, &rwa_token, collateral_received)?;
+
+            if lot_value > max_lot_value {
+                lot_filled = lot_filled
+                    .checked_mul(max_lot_value)
+                    .ok_or(Error::ArithmeticError)?
+                    .checked_div(lot_value)
+                    .ok_or(Error::ArithmeticError)?;
+                bid_filled = bid_filled
+                    .checked_mul(max_lot_value)
+                    .ok_or(Error::ArithmeticError)?
+                    .checked_div(lot_value)
+                    .ok_or(Error::ArithmeticError)?;
+                collateral_received = lot_filled
+                    .checked_mul(lot_modifier)
+                    .ok_or(Error::ArithmeticError)?
+                    .checked_div(SCALAR_12)
+                    .ok_or(Error::ArithmeticError)?;
+            }
+        }
+
+        let debt_to_pay = bid_filled
+            .checked_mul(bid_modifier)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_12)
+            .ok_or(Error::ArithmeticError)?;
+
+        Ok(PartialFill {
+            rwa_token,
+            debt_token_address,
+            lot_total,
+            bid_total,
+            lot_filled,
+            bid_filled,
+            collateral_received,
+            debt_to_pay,
+        })
+    }
+
     /// Calculate auction modifiers (lot modifier and bid modifier)
     /// Modifiers use SCALAR_12 (12 decimals)
     fn calculate_auction_modifiers(blocks_elapsed: u32) -> (i128, i128) {
@@ -7,4 +7,3 @@ pub mod interest_auction;
 pub mod lending;
 pub mod liquidations;
 pub mod oracles;
-
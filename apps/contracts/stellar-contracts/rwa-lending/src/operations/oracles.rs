@@ -1,8 +1,8 @@
-use soroban_sdk::{Address, Env, Symbol};
+use soroban_sdk::{Address, Env, Symbol, token::TokenClient};
 
 use crate::common::error::Error;
 use crate::common::storage::Storage;
-use crate::common::types::PriceData;
+use crate::common::types::{PriceData, USD_DECIMALS};
 use crate::rwa_oracle::{self, Asset};
 
 /// Oracle integration for fetching prices
@@ -19,32 +19,32 @@ impl Oracles {
         // Get the pegged asset symbol from the RWA Oracle
         // The oracle maintains a mapping from token contract address to asset symbol
         let pegged_asset = oracle_client.get_asset_id_from_token(rwa_token);
-        
+
         // Convert symbol to Asset::Other (the oracle stores RWA assets as Other(symbol))
         let asset = Asset::Other(pegged_asset);
-        
+
         // Get last price from oracle (SEP-40 compatible)
         let oracle_price_data = oracle_client
             .lastprice(&asset)
             .ok_or(Error::OraclePriceFetchFailed)?;
-        
+
         // Validate price data
         if oracle_price_data.price <= 0 {
             return Err(Error::InvalidOraclePrice);
         }
-        
+
         // Check if price is too old (more than 24 hours)
         let current_time = env.ledger().timestamp();
         if oracle_price_data.timestamp + 24 * 60 * 60 < current_time {
             return Err(Error::InvalidOraclePrice);
         }
-        
+
         // Convert rwa_oracle::PriceData to types::PriceData
         let price_data = PriceData {
             price: oracle_price_data.price,
             timestamp: oracle_price_data.timestamp,
         };
-        
+
         Ok(price_data)
     }
 
@@ -52,89 +52,147 @@ impl Oracles {
     /// The Reflector Oracle implements SEP-40, so we use Asset::Other(symbol) to query prices
     pub fn get_crypto_price(env: &Env, asset: &Symbol) -> Result<PriceData, Error> {
         let storage = Storage::get(env);
-        
+
         // Reflector Oracle implements SEP-40 interface (same as RWA Oracle)
         // We reuse rwa_oracle::Client here because both oracles share the same SEP-40 interface.
         // The client is generic - it works with any contract implementing SEP-40 methods.
         // The Reflector Oracle contract address is stored in storage.reflector_oracle
         let oracle_client = rwa_oracle::Client::new(env, &storage.reflector_oracle);
-        
+
         // Convert Symbol to Asset::Other (for crypto assets like XLM, USDC, etc.)
         let asset_enum = Asset::Other(asset.clone());
-        
+
         // Get last price from Reflector Oracle (SEP-40 compatible)
         let oracle_price_data = oracle_client
             .lastprice(&asset_enum)
             .ok_or(Error::OraclePriceFetchFailed)?;
-        
+
         // Validate price data
         if oracle_price_data.price <= 0 {
             return Err(Error::InvalidOraclePrice);
         }
-        
+
         // Check if price is too old (more than 24 hours)
         let current_time = env.ledger().timestamp();
         if oracle_price_data.timestamp + 24 * 60 * 60 < current_time {
             return Err(Error::InvalidOraclePrice);
         }
-        
+
         // Convert rwa_oracle::PriceData to types::PriceData
         let price_data = PriceData {
             price: oracle_price_data.price,
             timestamp: oracle_price_data.timestamp,
         };
-        
+
         Ok(price_data)
     }
 
+    /// Ensure an RWA token's price is fresh enough to back new borrows
+    /// Fails with `OraclePriceStale` when the price is older than the token's
+    /// `max_price_age`, or when the token was flagged stale and the oracle has
+    /// not published a newer price since
+    pub fn require_fresh_rwa_price(env: &Env, rwa_token: &Address) -> Result<(), Error> {
+        let price_data = Self::get_rwa_price(env, rwa_token)?;
+        let storage = Storage::get(env);
+
+        let max_age = storage.max_price_ages.get(rwa_token.clone()).unwrap_or(0);
+        if max_age > 0 && price_data.timestamp.saturating_add(max_age) < env.ledger().timestamp() {
+            return Err(Error::OraclePriceStale);
+        }
+
+        if let Some(flagged_at) = storage.stale_collateral.get(rwa_token.clone())
+            && price_data.timestamp <= flagged_at
+        {
+            return Err(Error::OraclePriceStale);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the Reflector Oracle has published any price for a crypto asset
+    pub fn has_crypto_price(env: &Env, asset: &Symbol) -> bool {
+        let storage = Storage::get(env);
+        let oracle_client = rwa_oracle::Client::new(env, &storage.reflector_oracle);
+        oracle_client
+            .lastprice(&Asset::Other(asset.clone()))
+            .is_some()
+    }
+
     /// Get price with decimals from RWA Oracle
     pub fn get_rwa_price_with_decimals(
         env: &Env,
         rwa_token: &Address,
     ) -> Result<(i128, u32), Error> {
         let price_data = Self::get_rwa_price(env, rwa_token)?;
-        
+
         let storage = Storage::get(env);
         let oracle_client = rwa_oracle::Client::new(env, &storage.rwa_oracle);
-        
+
         // Get decimals from oracle (SEP-40 compatible)
         let decimals = oracle_client.decimals();
-        
+
         Ok((price_data.price, decimals))
     }
 
     /// Get price with decimals from Reflector Oracle
-    pub fn get_crypto_price_with_decimals(
-        env: &Env,
-        asset: &Symbol,
-    ) -> Result<(i128, u32), Error> {
+    pub fn get_crypto_price_with_decimals(env: &Env, asset: &Symbol) -> Result<(i128, u32), Error> {
         let price_data = Self::get_crypto_price(env, asset)?;
-        
+
         let storage = Storage::get(env);
         let oracle_client = rwa_oracle::Client::new(env, &storage.reflector_oracle);
-        
+
         // Get decimals from Reflector Oracle (SEP-40 compatible)
         let decimals = oracle_client.decimals();
-        
+
         Ok((price_data.price, decimals))
     }
 
+    /// USD value (`USD_DECIMALS`) of an RWA token amount
+    /// Price decimals come from the RWA Oracle, token decimals from the token contract
+    pub fn rwa_usd_value(env: &Env, rwa_token: &Address, amount: i128) -> Result<i128, Error> {
+        let (price, price_decimals) = Self::get_rwa_price_with_decimals(env, rwa_token)?;
+        let asset_decimals = TokenClient::new(env, rwa_token).decimals();
+
+        Self::calculate_usd_value(env, amount, price, asset_decimals, price_decimals)
+    }
+
+    /// USD value (`USD_DECIMALS`) of a crypto asset amount
+    /// Price decimals come from the Reflector Oracle, token decimals from the token contract
+    pub fn crypto_usd_value(env: &Env, asset: &Symbol, amount: i128) -> Result<i128, Error> {
+        let (price, price_decimals) = Self::get_crypto_price_with_decimals(env, asset)?;
+        let token_address =
+            Storage::get_token_contract(env, asset).ok_or(Error::TokenContractNotSet)?;
+        let asset_decimals = TokenClient::new(env, &token_address).decimals();
+
+        Self::calculate_usd_value(env, amount, price, asset_decimals, price_decimals)
+    }
+
     /// Calculate USD value of an amount
-    /// Formula: value = (amount * price) / 10^(price_decimals)
-    /// The price is already in the oracle's scale (price_decimals), so we just multiply and divide
+    /// Formula: value = (amount * price) / 10^(price_decimals), rescaled from
+    /// asset_decimals to USD_DECIMALS
+    /// `price_decimals` must be the decimals reported by the oracle that quoted `price`
     pub fn calculate_usd_value(
         _env: &Env,
         amount: i128,
         price: i128,
-        _asset_decimals: u32,
+        asset_decimals: u32,
         price_decimals: u32,
     ) -> Result<i128, Error> {
-        // Multiply amount by price, then divide by 10^(price_decimals) to get USD value
-        let value = amount
-            .checked_mul(price)
-            .ok_or(Error::ArithmeticError)?;
-        
-        Ok(value / 10i128.pow(price_decimals))
+        // Multiply amount by price, then divide by 10^(price_decimals): value in asset units
+        let value =
+            amount.checked_mul(price).ok_or(Error::ArithmeticError)? / 10i128.pow(price_decimals);
+
+        Self::rescale(value, asset_decimals, USD_DECIMALS)
     }
-}
 
+    /// Rescale a value between decimal scales (rounds down)
+    pub fn rescale(value: i128, from_decimals: u32, to_decimals: u32) -> Result<i128, Error> {
+        if to_decimals >= from_decimals {
+            value
+                .checked_mul(10i128.pow(to_decimals - from_decimals))
+                .ok_or(Error::ArithmeticError)
+        } else {
+            Ok(value / 10i128.pow(from_decimals - to_decimals))
+        }
+    }
+}
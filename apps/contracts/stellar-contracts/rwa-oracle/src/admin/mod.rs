@@ -1,5 +1,7 @@
 use soroban_sdk::{Address, BytesN, Env};
 
+use crate::{Asset, RoundingMode};
+
 use crate::common::storage::RWAOracleStorage;
 use crate::common::types::{ADMIN_KEY, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
 
@@ -41,6 +43,95 @@ impl Admin {
         Self::extend_instance_ttl(env);
     }
 
+    /// Set the minimum number of fresh sources required for a median price
+    pub fn set_min_sources(env: &Env, min_sources: u32) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.min_sources = min_sources;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set the maximum number of price sources per asset (0 = unlimited)
+    pub fn set_max_sources(env: &Env, max_sources: u32) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.max_sources = max_sources;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set the maximum number of records returned by prices()
+    pub fn set_max_prices_query(env: &Env, max_records: u32) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.max_prices_query = max_records;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set how far ahead of ledger time a price timestamp may be
+    pub fn set_max_future_drift(env: &Env, max_seconds: u64) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.max_future_drift = max_seconds;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Freeze or unfreeze price updates for a single asset
+    pub fn set_asset_frozen(env: &Env, asset: Asset, frozen: bool) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        if frozen {
+            state.frozen_assets.set(asset, true);
+        } else {
+            state.frozen_assets.remove(asset);
+        }
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set whether reads of a frozen asset fail instead of returning the last good price
+    pub fn set_frozen_reads_fail(env: &Env, fail: bool) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.frozen_reads_fail = fail;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set the grace period (seconds) beyond max staleness for an asset (0 removes it)
+    pub fn set_stale_grace(env: &Env, asset: Asset, grace_seconds: u64) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        if grace_seconds > 0 {
+            state.stale_grace.set(asset, grace_seconds);
+        } else {
+            state.stale_grace.remove(asset);
+        }
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set whether batch price updates emit only the batch summary event
+    pub fn set_batch_events_only(env: &Env, batch_only: bool) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.batch_events_only = batch_only;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Set the rounding mode used by normalized_price
+    pub fn set_rounding_mode(env: &Env, mode: RoundingMode) {
+        Self::require_admin(env);
+        let mut state = RWAOracleStorage::get(env);
+        state.rounding_mode = mode;
+        RWAOracleStorage::set(env, &state);
+        Self::extend_instance_ttl(env);
+    }
+
     /// Extend instance TTL
     pub fn extend_instance_ttl(env: &Env) {
         env.storage()
@@ -27,4 +27,19 @@ pub enum Error {
 
     /// Timestamp is too old or not strictly increasing
     TimestampTooOld = 8,
+
+    /// Not enough fresh price sources to compute a median
+    InsufficientSources = 9,
+
+    /// Asset is frozen by the admin
+    OraclePaused = 10,
+
+    /// Asset already has the maximum number of price sources
+    TooManySources = 11,
+
+    /// Decimal rescaling factor is out of range
+    InvalidDecimals = 12,
+
+    /// Confidence is above the maximum (10_000 basis points)
+    InvalidConfidence = 13,
 }
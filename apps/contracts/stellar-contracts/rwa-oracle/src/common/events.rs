@@ -0,0 +1,44 @@
+use soroban_sdk::{contractevent, BytesN, Env};
+
+use crate::Asset;
+
+/// Emitted when a new price is recorded for an asset
+#[contractevent]
+pub struct PriceUpdated {
+    #[topic]
+    pub asset: Asset,
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted once per multi-asset `set_asset_prices` update
+#[contractevent]
+pub struct PricesBatchUpdated {
+    pub count: u32,
+    /// SHA-256 of the XDR-encoded list of updated assets
+    pub assets_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+/// Event emission utilities
+pub struct Events;
+
+impl Events {
+    pub fn price_updated(env: &Env, asset: &Asset, price: i128, timestamp: u64) {
+        PriceUpdated {
+            asset: asset.clone(),
+            price,
+            timestamp,
+        }
+        .publish(env);
+    }
+
+    pub fn prices_batch_updated(env: &Env, count: u32, assets_hash: BytesN<32>, timestamp: u64) {
+        PricesBatchUpdated {
+            count,
+            assets_hash,
+            timestamp,
+        }
+        .publish(env);
+    }
+}
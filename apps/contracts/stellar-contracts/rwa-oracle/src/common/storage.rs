@@ -1,9 +1,12 @@
 use soroban_sdk::{Env, Map, Vec};
 
 use crate::rwa::types::{RWAAssetType, RWAMetadata};
-use crate::{Asset, Symbol, contracttype};
+use crate::{Asset, RoundingMode, Symbol, contracttype};
 
-use super::types::{DEFAULT_MAX_STALENESS, STORAGE};
+use super::types::{
+    DEFAULT_MAX_FUTURE_DRIFT, DEFAULT_MAX_PRICES_QUERY, DEFAULT_MAX_STALENESS, DEFAULT_MIN_SOURCES,
+    STORAGE,
+};
 
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -20,6 +23,24 @@ pub struct RWAOracleStorage {
     pub asset_types: Map<Asset, RWAAssetType>,
     // Maximum acceptable age for price data (seconds)
     pub max_staleness: u64,
+    // Minimum number of fresh sources required by median_price
+    pub min_sources: u32,
+    // Maximum number of price sources per asset (0 = unlimited)
+    pub max_sources: u32,
+    // Maximum number of records returned by prices()
+    pub max_prices_query: u32,
+    // Maximum seconds a price timestamp may be ahead of ledger time
+    pub max_future_drift: u64,
+    // Assets whose price writes (and optionally reads) are frozen
+    pub frozen_assets: Map<Asset, bool>,
+    // Whether reads of a frozen asset fail instead of returning the last good price
+    pub frozen_reads_fail: bool,
+    // Per-asset grace (seconds) beyond max_staleness during which lastprice_checked degrades
+    pub stale_grace: Map<Asset, u64>,
+    // Whether set_asset_prices emits only the batch event (no per-asset events)
+    pub batch_events_only: bool,
+    // Rounding applied by normalized_price when dropping decimals
+    pub rounding_mode: RoundingMode,
 }
 
 impl RWAOracleStorage {
@@ -33,6 +54,15 @@ impl RWAOracleStorage {
             rwa_metadata: Map::new(env),
             asset_types: Map::new(env),
             max_staleness: DEFAULT_MAX_STALENESS,
+            min_sources: DEFAULT_MIN_SOURCES,
+            max_sources: 0,
+            max_prices_query: DEFAULT_MAX_PRICES_QUERY,
+            max_future_drift: DEFAULT_MAX_FUTURE_DRIFT,
+            frozen_assets: Map::new(env),
+            frozen_reads_fail: false,
+            stale_grace: Map::new(env),
+            batch_events_only: false,
+            rounding_mode: RoundingMode::Floor,
         }
     }
 
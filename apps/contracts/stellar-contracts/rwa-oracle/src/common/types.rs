@@ -15,14 +15,25 @@ pub const INSTANCE_BUMP_AMOUNT: u32 = 518_400;
 pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = 17_280;
 pub const PERSISTENT_BUMP_AMOUNT: u32 = 518_400;
 
-// Timestamp drift tolerance
-pub const MAX_TIMESTAMP_DRIFT_SECONDS: u64 = 300;
+// Default tolerance for price timestamps ahead of ledger time
+pub const DEFAULT_MAX_FUTURE_DRIFT: u64 = 300;
 
 // Default max staleness: 24 hours
 pub const DEFAULT_MAX_STALENESS: u64 = 86_400;
 
+// Default minimum number of fresh sources required for a median price
+pub const DEFAULT_MIN_SOURCES: u32 = 1;
+
+// Price confidence is expressed in basis points; prices recorded without one get the maximum
+pub const MAX_CONFIDENCE: u32 = 10_000;
+
+// Default maximum number of records returned by prices()
+pub const DEFAULT_MAX_PRICES_QUERY: u32 = 200;
+
 #[contracttype]
 pub enum DataKey {
     Prices(Asset),
     TokenToAsset(Address), // Map token contract address to asset Symbol
+    SourcePrices(Asset),   // Latest price reported by each source for an asset
+    Confidence(Asset),     // (timestamp, confidence) of the latest confidence-tagged price
 }
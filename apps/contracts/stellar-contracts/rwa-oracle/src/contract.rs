@@ -1,17 +1,19 @@
 use soroban_sdk::{
-    contract, contractimpl, panic_with_error, Address, BytesN, Env, Map, Symbol, Vec,
+    contract, contractimpl, panic_with_error, xdr::ToXdr, Address, BytesN, Env, Map, Symbol,
+    Vec,
 };
 
 use crate::admin::Admin;
 use crate::common::error::Error;
+use crate::common::events::Events;
 use crate::common::storage::RWAOracleStorage;
 use crate::common::types::{
-    DataKey, MAX_PRICE_HISTORY, MAX_TIMESTAMP_DRIFT_SECONDS, PERSISTENT_BUMP_AMOUNT,
+    DataKey, MAX_CONFIDENCE, MAX_PRICE_HISTORY, PERSISTENT_BUMP_AMOUNT,
     PERSISTENT_LIFETIME_THRESHOLD,
 };
 use crate::rwa::types::{RWAAssetType, RWAMetadata, TokenizationInfo};
 use crate::sep40::{IsSep40, IsSep40Admin};
-use crate::{Asset, PriceData};
+use crate::{Asset, CheckedPriceData, PriceData, RoundingMode};
 
 fn new_asset_prices_map(env: &Env) -> Map<u64, i128> {
     Map::new(env)
@@ -103,6 +105,346 @@ impl RWAOracle {
         Admin::set_max_staleness(env, max_seconds);
     }
 
+    /// Set the maximum number of records returned by `prices`
+    pub fn set_max_prices_query(env: &Env, max_records: u32) {
+        Admin::set_max_prices_query(env, max_records);
+    }
+
+    /// Set how far ahead of ledger time (in seconds) a price timestamp may be
+    pub fn set_max_future_drift(env: &Env, max_seconds: u64) {
+        Admin::set_max_future_drift(env, max_seconds);
+    }
+
+    // ==================== Emergency Freeze Functions ====================
+
+    /// Freeze a single asset: price updates revert with `OraclePaused`
+    pub fn freeze_asset(env: &Env, asset: Asset) {
+        if !RWAOracleStorage::get(env).assets.contains(&asset) {
+            panic_with_error!(env, Error::AssetNotFound);
+        }
+        Admin::set_asset_frozen(env, asset, true);
+    }
+
+    /// Unfreeze a previously frozen asset
+    pub fn unfreeze_asset(env: &Env, asset: Asset) {
+        Admin::set_asset_frozen(env, asset, false);
+    }
+
+    /// Check whether an asset is frozen
+    pub fn is_asset_frozen(env: &Env, asset: Asset) -> bool {
+        RWAOracleStorage::get(env).frozen_assets.contains_key(asset)
+    }
+
+    /// Set whether reads of a frozen asset revert with `OraclePaused`
+    /// instead of returning the last good price (default: false)
+    pub fn set_frozen_reads_fail(env: &Env, fail: bool) {
+        Admin::set_frozen_reads_fail(env, fail);
+    }
+
+    /// Get whether reads of a frozen asset revert
+    pub fn frozen_reads_fail(env: &Env) -> bool {
+        RWAOracleStorage::get(env).frozen_reads_fail
+    }
+
+    // ==================== Staleness Grace Functions ====================
+
+    /// Set the grace period (seconds) beyond `max_staleness` during which
+    /// `lastprice_checked` still returns the last price, flagged as degraded
+    pub fn set_stale_grace_seconds(env: &Env, asset: Asset, grace_seconds: u64) {
+        Admin::set_stale_grace(env, asset, grace_seconds);
+    }
+
+    /// Get the staleness grace period for an asset (0 if none)
+    pub fn stale_grace_seconds(env: &Env, asset: Asset) -> u64 {
+        RWAOracleStorage::get(env).stale_grace.get(asset).unwrap_or(0)
+    }
+
+    /// Get the last price together with a staleness flag.
+    /// Prices within `max_staleness` are returned clean, prices within the
+    /// asset's grace beyond that are returned with `degraded = true`, and
+    /// anything older returns `None`.
+    pub fn lastprice_checked(env: &Env, asset: Asset) -> Option<CheckedPriceData> {
+        let last = <Self as IsSep40>::lastprice(env, asset.clone())?;
+        let state = RWAOracleStorage::get(env);
+        let grace = state.stale_grace.get(asset).unwrap_or(0);
+        let age = env.ledger().timestamp().saturating_sub(last.timestamp);
+
+        if age > state.max_staleness.saturating_add(grace) {
+            return None;
+        }
+
+        Some(CheckedPriceData {
+            price: last.price,
+            timestamp: last.timestamp,
+            degraded: age > state.max_staleness,
+        })
+    }
+
+    // ==================== Batch Update Functions ====================
+
+    /// Record prices for several assets at the same timestamp (admin only).
+    ///
+    /// Emits one `prices_batch_updated` event with the number of assets and a
+    /// SHA-256 of the updated asset list. Per-asset `price_updated` events are
+    /// also emitted unless `batch_events_only` is enabled.
+    pub fn set_asset_prices(env: &Env, prices: Vec<(Asset, i128)>, timestamp: u64) {
+        Admin::require_admin(env);
+        let emit_asset_events = !RWAOracleStorage::get(env).batch_events_only;
+
+        let mut assets = Vec::new(env);
+        for (asset_id, price) in prices.iter() {
+            Self::set_asset_price_internal(env, asset_id.clone(), price, timestamp, emit_asset_events);
+            assets.push_back(asset_id);
+        }
+
+        let assets_hash: BytesN<32> = env.crypto().sha256(&assets.clone().to_xdr(env)).into();
+        Events::prices_batch_updated(env, assets.len(), assets_hash, timestamp);
+    }
+
+    /// Set whether `set_asset_prices` emits only the batch event
+    pub fn set_batch_events_only(env: &Env, batch_only: bool) {
+        Admin::set_batch_events_only(env, batch_only);
+    }
+
+    /// Whether `set_asset_prices` emits only the batch event
+    pub fn batch_events_only(env: &Env) -> bool {
+        RWAOracleStorage::get(env).batch_events_only
+    }
+
+    // ==================== Health Functions ====================
+
+    /// Get the timestamp of the latest stored price sample for an asset.
+    /// Unlike `lastprice`, this never reverts for frozen assets so monitors
+    /// can keep tracking them.
+    pub fn last_update_timestamp(env: &Env, asset: Asset) -> Option<u64> {
+        Self::get_asset_price(env, asset)?.keys().last()
+    }
+
+    /// Get the seconds elapsed between the latest stored price sample and
+    /// ledger time, or `None` if the asset has never been updated
+    pub fn seconds_since_update(env: &Env, asset: Asset) -> Option<u64> {
+        let last = Self::last_update_timestamp(env, asset)?;
+        Some(env.ledger().timestamp().saturating_sub(last))
+    }
+
+    // ==================== Price Normalization Functions ====================
+
+    /// Rescale a raw price from the oracle's `decimals` to `target_decimals`.
+    ///
+    /// This is the canonical rounding consumers should use so their values
+    /// match the oracle's. Scaling up is exact. Scaling down either drops the
+    /// extra digits (`Floor`) or rounds to the closest value with ties rounding
+    /// up (`Nearest`): a 7-decimal `1_234_567` at 2 decimals is `12` with
+    /// `Floor` and `12` with `Nearest`, while `1_250_000` is `12` and `13`.
+    /// Reverts with `InvalidDecimals` if the scaling factor overflows.
+    pub fn round_price(env: &Env, raw: i128, target_decimals: u32, mode: RoundingMode) -> i128 {
+        let decimals = RWAOracleStorage::get(env).decimals;
+        Self::rescale_price(env, raw, decimals, target_decimals, mode)
+    }
+
+    /// Get the latest price for an asset rescaled to `target_decimals` with
+    /// the configured rounding mode, or `None` if the asset has no price
+    pub fn normalized_price(env: &Env, asset: Asset, target_decimals: u32) -> Option<i128> {
+        let last = <Self as IsSep40>::lastprice(env, asset)?;
+        let mode = RWAOracleStorage::get(env).rounding_mode;
+        Some(Self::round_price(env, last.price, target_decimals, mode))
+    }
+
+    /// Set the rounding mode used by `normalized_price` (default: `Floor`)
+    pub fn set_rounding_mode(env: &Env, mode: RoundingMode) {
+        Admin::set_rounding_mode(env, mode);
+    }
+
+    /// Get the rounding mode used by `normalized_price`
+    pub fn rounding_mode(env: &Env) -> RoundingMode {
+        RWAOracleStorage::get(env).rounding_mode
+    }
+
+    // ==================== Price Confidence Functions ====================
+
+    /// Record a price together with its confidence in basis points (admin only).
+    /// Lower values flag wide-spread or backup-feed-derived prices; prices set
+    /// through `set_asset_price` carry the maximum confidence (10_000).
+    pub fn set_asset_price_with_confidence(
+        env: &Env,
+        asset_id: Asset,
+        price: i128,
+        timestamp: u64,
+        confidence: u32,
+    ) {
+        Admin::require_admin(env);
+        if confidence > MAX_CONFIDENCE {
+            panic_with_error!(env, Error::InvalidConfidence);
+        }
+
+        Self::set_asset_price_internal(env, asset_id.clone(), price, timestamp, true);
+
+        let key = DataKey::Confidence(asset_id);
+        env.storage().persistent().set(&key, &(timestamp, confidence));
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Get the confidence (basis points) of the latest price, or `None` if the
+    /// asset has no price
+    pub fn price_confidence(env: &Env, asset: Asset) -> Option<u32> {
+        let last = <Self as IsSep40>::lastprice(env, asset.clone())?;
+        let tagged: Option<(u64, u32)> =
+            env.storage().persistent().get(&DataKey::Confidence(asset));
+        match tagged {
+            Some((timestamp, confidence)) if timestamp == last.timestamp => Some(confidence),
+            _ => Some(MAX_CONFIDENCE),
+        }
+    }
+
+    /// Get the last price only if its confidence is at least `min_confidence`
+    /// (basis points); returns `None` when below the threshold or unpriced
+    pub fn lastprice_if_confident(env: &Env, asset: Asset, min_confidence: u32) -> Option<PriceData> {
+        let confidence = Self::price_confidence(env, asset.clone())?;
+        if confidence < min_confidence {
+            return None;
+        }
+        <Self as IsSep40>::lastprice(env, asset)
+    }
+
+    // ==================== Multi-Source Functions ====================
+
+    /// Record the latest price reported by an independent source for an asset
+    pub fn set_asset_price_from(
+        env: &Env,
+        source_id: Symbol,
+        asset_id: Asset,
+        price: i128,
+        timestamp: u64,
+    ) {
+        Admin::require_admin(env);
+        Self::require_not_frozen(env, &asset_id);
+        if price <= 0 {
+            panic_with_error!(env, Error::InvalidPrice);
+        }
+
+        let current_time = env.ledger().timestamp();
+        if timestamp > current_time.saturating_add(RWAOracleStorage::get(env).max_future_drift) {
+            panic_with_error!(env, Error::TimestampInFuture);
+        }
+
+        if !RWAOracleStorage::get(env).assets.contains(&asset_id) {
+            panic_with_error!(env, Error::AssetNotFound);
+        }
+
+        let mut sources = Self::get_source_prices_map(env, asset_id.clone());
+        match sources.get(source_id.clone()) {
+            Some(last_price) => {
+                if timestamp <= last_price.timestamp {
+                    panic_with_error!(env, Error::TimestampTooOld);
+                }
+            }
+            None => {
+                let max_sources = RWAOracleStorage::get(env).max_sources;
+                if max_sources > 0 && sources.len() >= max_sources {
+                    panic_with_error!(env, Error::TooManySources);
+                }
+            }
+        }
+
+        sources.set(source_id, PriceData { price, timestamp });
+        let key = DataKey::SourcePrices(asset_id);
+        env.storage().persistent().set(&key, &sources);
+
+        Admin::extend_instance_ttl(env);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Set the minimum number of fresh sources required by `median_price`
+    pub fn set_min_sources(env: &Env, min_sources: u32) {
+        Admin::set_min_sources(env, min_sources);
+    }
+
+    /// Get the minimum number of fresh sources required by `median_price`
+    pub fn min_sources(env: &Env) -> u32 {
+        RWAOracleStorage::get(env).min_sources
+    }
+
+    /// Set the maximum number of sources that may report for an asset (0 = unlimited)
+    pub fn set_max_sources(env: &Env, max_sources: u32) {
+        Admin::set_max_sources(env, max_sources);
+    }
+
+    /// Get the maximum number of sources that may report for an asset
+    pub fn max_sources(env: &Env) -> u32 {
+        RWAOracleStorage::get(env).max_sources
+    }
+
+    /// Get the latest price reported by each source for an asset
+    pub fn get_source_prices(env: &Env, asset_id: Asset) -> Vec<(Symbol, PriceData)> {
+        let mut prices = Vec::new(env);
+        for (source_id, data) in Self::get_source_prices_map(env, asset_id).iter() {
+            prices.push_back((source_id, data));
+        }
+        prices
+    }
+
+    /// Get the sources whose latest report for an asset is older than
+    /// `max_staleness`. These are the sources `median_price` ignores.
+    pub fn get_stale_sources(env: &Env, asset_id: Asset) -> Vec<Symbol> {
+        let max_staleness = RWAOracleStorage::get(env).max_staleness;
+        let current_time = env.ledger().timestamp();
+
+        let mut stale = Vec::new(env);
+        for (source_id, data) in Self::get_source_prices_map(env, asset_id).iter() {
+            if Self::is_source_stale(current_time, &data, max_staleness) {
+                stale.push_back(source_id);
+            }
+        }
+        stale
+    }
+
+    /// Get the median of the latest prices reported by fresh sources.
+    ///
+    /// Sources whose latest report is older than `max_staleness` are ignored.
+    /// With an even number of fresh sources the two middle prices are averaged.
+    /// The returned timestamp is the oldest timestamp among the fresh sources.
+    pub fn median_price(env: &Env, asset_id: Asset) -> Result<PriceData, Error> {
+        let state = RWAOracleStorage::get(env);
+        if state.frozen_reads_fail && state.frozen_assets.contains_key(asset_id.clone()) {
+            return Err(Error::OraclePaused);
+        }
+        let current_time = env.ledger().timestamp();
+
+        let mut sorted: Vec<i128> = Vec::new(env);
+        let mut oldest_timestamp = u64::MAX;
+        for (_, data) in Self::get_source_prices_map(env, asset_id).iter() {
+            if Self::is_source_stale(current_time, &data, state.max_staleness) {
+                continue;
+            }
+
+            // Insertion sort keeps the fresh prices ordered
+            let mut index = 0;
+            while index < sorted.len() && sorted.get_unchecked(index) < data.price {
+                index += 1;
+            }
+            sorted.insert(index, data.price);
+            oldest_timestamp = oldest_timestamp.min(data.timestamp);
+        }
+
+        let count = sorted.len();
+        if count == 0 || count < state.min_sources {
+            return Err(Error::InsufficientSources);
+        }
+
+        let mid = count / 2;
+        let price = if count.is_multiple_of(2) {
+            let lower = sorted.get_unchecked(mid - 1);
+            lower + (sorted.get_unchecked(mid) - lower) / 2
+        } else {
+            sorted.get_unchecked(mid)
+        };
+
+        Ok(PriceData {
+            price,
+            timestamp: oldest_timestamp,
+        })
+    }
+
     // ==================== RWA Query Functions ====================
 
     /// Get complete RWA metadata for an asset
@@ -151,14 +493,14 @@ impl RWAOracle {
         // Fallback: iterate through metadata to find matching token_contract
         let state = RWAOracleStorage::get(env);
         for (asset_id, metadata) in state.rwa_metadata.iter() {
-            if let Some(token_contract) = &metadata.tokenization_info.token_contract {
-                if token_contract == token_address {
-                    // Cache the mapping for future lookups
-                    env.storage()
-                        .persistent()
-                        .set(&DataKey::TokenToAsset(token_address.clone()), &asset_id);
-                    return Ok(asset_id);
-                }
+            if let Some(token_contract) = &metadata.tokenization_info.token_contract
+                && token_contract == token_address
+            {
+                // Cache the mapping for future lookups
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::TokenToAsset(token_address.clone()), &asset_id);
+                return Ok(asset_id);
             }
         }
 
@@ -171,26 +513,52 @@ impl RWAOracle {
         state.max_staleness
     }
 
+    /// Get the maximum number of records returned by `prices`
+    pub fn max_prices_query(env: &Env) -> u32 {
+        let state = RWAOracleStorage::get(env);
+        state.max_prices_query
+    }
+
+    /// Get the maximum seconds a price timestamp may be ahead of ledger time
+    pub fn max_future_drift(env: &Env) -> u64 {
+        let state = RWAOracleStorage::get(env);
+        state.max_future_drift
+    }
+
     // ==================== Internal Helpers ====================
 
     fn get_asset_price(env: &Env, asset_id: Asset) -> Option<Map<u64, i128>> {
         env.storage().persistent().get(&DataKey::Prices(asset_id))
     }
 
-    fn set_asset_price_internal(env: &Env, asset_id: Asset, price: i128, timestamp: u64) {
+    fn get_source_prices_map(env: &Env, asset_id: Asset) -> Map<Symbol, PriceData> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SourcePrices(asset_id))
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn set_asset_price_internal(
+        env: &Env,
+        asset_id: Asset,
+        price: i128,
+        timestamp: u64,
+        emit_event: bool,
+    ) {
+        Self::require_not_frozen(env, &asset_id);
         if price <= 0 {
             panic_with_error!(env, Error::InvalidPrice);
         }
 
         let current_time = env.ledger().timestamp();
-        if timestamp > current_time + MAX_TIMESTAMP_DRIFT_SECONDS {
+        if timestamp > current_time.saturating_add(RWAOracleStorage::get(env).max_future_drift) {
             panic_with_error!(env, Error::TimestampInFuture);
         }
 
-        if let Some(last_price) = <Self as IsSep40>::lastprice(env, asset_id.clone()) {
-            if timestamp <= last_price.timestamp {
-                panic_with_error!(env, Error::TimestampTooOld);
-            }
+        if let Some(last_price) = <Self as IsSep40>::lastprice(env, asset_id.clone())
+            && timestamp <= last_price.timestamp
+        {
+            panic_with_error!(env, Error::TimestampTooOld);
         }
 
         let mut asset = Self::get_asset_price(env, asset_id.clone()).unwrap_or_else(|| {
@@ -214,10 +582,74 @@ impl RWAOracle {
         state.last_timestamp = timestamp;
         RWAOracleStorage::set(env, &state);
 
+        if emit_event {
+            Events::price_updated(env, &asset_id, price, timestamp);
+        }
+
         Admin::extend_instance_ttl(env);
         Self::extend_persistent_ttl(env, &DataKey::Prices(asset_id));
     }
 
+    fn rescale_price(
+        env: &Env,
+        raw: i128,
+        from_decimals: u32,
+        to_decimals: u32,
+        mode: RoundingMode,
+    ) -> i128 {
+        let pow10 = |exp: u32| {
+            10i128
+                .checked_pow(exp)
+                .unwrap_or_else(|| panic_with_error!(env, Error::InvalidDecimals))
+        };
+
+        if to_decimals >= from_decimals {
+            return raw
+                .checked_mul(pow10(to_decimals - from_decimals))
+                .unwrap_or_else(|| panic_with_error!(env, Error::InvalidDecimals));
+        }
+
+        let divisor = pow10(from_decimals - to_decimals);
+        let floor = raw.div_euclid(divisor);
+        match mode {
+            RoundingMode::Floor => floor,
+            RoundingMode::Nearest => {
+                if raw.rem_euclid(divisor) >= divisor / 2 {
+                    floor + 1
+                } else {
+                    floor
+                }
+            }
+            RoundingMode::Ceil => {
+                if raw.rem_euclid(divisor) > 0 {
+                    floor + 1
+                } else {
+                    floor
+                }
+            }
+        }
+    }
+
+    /// Whether a source's latest report is older than `max_staleness`
+    fn is_source_stale(current_time: u64, data: &PriceData, max_staleness: u64) -> bool {
+        current_time.saturating_sub(data.timestamp) > max_staleness
+    }
+
+    /// Revert with `OraclePaused` if the asset is frozen
+    fn require_not_frozen(env: &Env, asset_id: &Asset) {
+        if RWAOracleStorage::get(env).frozen_assets.contains_key(asset_id.clone()) {
+            panic_with_error!(env, Error::OraclePaused);
+        }
+    }
+
+    /// Revert reads of a frozen asset with `OraclePaused` when configured to do so
+    fn check_frozen_read(env: &Env, asset_id: &Asset) {
+        let state = RWAOracleStorage::get(env);
+        if state.frozen_reads_fail && state.frozen_assets.contains_key(asset_id.clone()) {
+            panic_with_error!(env, Error::OraclePaused);
+        }
+    }
+
     fn extend_persistent_ttl(env: &Env, key: &DataKey) {
         env.storage()
             .persistent()
@@ -257,7 +689,7 @@ impl IsSep40Admin for RWAOracle {
 
     fn set_asset_price(env: &Env, asset_id: Asset, price: i128, timestamp: u64) {
         Admin::require_admin(env);
-        RWAOracle::set_asset_price_internal(env, asset_id, price, timestamp);
+        RWAOracle::set_asset_price_internal(env, asset_id, price, timestamp, true);
     }
 }
 
@@ -276,26 +708,25 @@ impl IsSep40 for RWAOracle {
     }
 
     fn lastprice(env: &Env, asset: Asset) -> Option<PriceData> {
-        let Some(asset_prices) = RWAOracle::get_asset_price(env, asset.clone()) else {
-            return None;
-        };
+        RWAOracle::check_frozen_read(env, &asset);
+        let asset_prices = RWAOracle::get_asset_price(env, asset.clone())?;
         let timestamp = asset_prices.keys().last()?;
         let price = asset_prices.get(timestamp)?;
         Some(PriceData { price, timestamp })
     }
 
     fn price(env: &Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
-        let Some(asset_prices) = RWAOracle::get_asset_price(env, asset.clone()) else {
-            return None;
-        };
+        RWAOracle::check_frozen_read(env, &asset);
+        let asset_prices = RWAOracle::get_asset_price(env, asset.clone())?;
         let price = asset_prices.get(timestamp)?;
         Some(PriceData { price, timestamp })
     }
 
+    /// Requests above `max_prices_query` return the capped number of records
     fn prices(env: &Env, asset: Asset, records: u32) -> Option<Vec<PriceData>> {
-        let Some(asset_prices) = RWAOracle::get_asset_price(env, asset.clone()) else {
-            return None;
-        };
+        RWAOracle::check_frozen_read(env, &asset);
+        let asset_prices = RWAOracle::get_asset_price(env, asset.clone())?;
+        let records = records.min(RWAOracleStorage::get(env).max_prices_query);
         let mut prices = Vec::new(env);
         asset_prices
             .keys()
@@ -31,5 +31,26 @@ pub struct PriceData {
     pub timestamp: u64, // recording timestamp
 }
 
+/// Rounding applied when a price is rescaled to fewer decimals
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Drop the extra digits (round toward negative infinity)
+    Floor,
+    /// Round to the closest value, ties rounding up
+    Nearest,
+    /// Round any dropped remainder up (toward positive infinity)
+    Ceil,
+}
+
+/// Last price with a staleness flag, returned by `lastprice_checked`
+#[contracttype]
+#[derive(Debug, Clone)]
+pub struct CheckedPriceData {
+    pub price: i128,    // last known price
+    pub timestamp: u64, // recording timestamp
+    pub degraded: bool, // true when older than max_staleness but within the grace window
+}
+
 #[cfg(test)]
 mod test;
@@ -1,10 +1,14 @@
 #![cfg(test)]
 extern crate std;
 
-use crate::{Asset, Error, RWAOracle, RWAOracleClient};
+use crate::common::types::MAX_PRICE_HISTORY;
+use crate::{Asset, Error, RWAOracle, RWAOracleClient, RoundingMode};
 use crate::{RWAAssetType, RWAMetadata, TokenizationInfo, ValuationMethod};
 
-use soroban_sdk::{testutils::Address as _, testutils::Ledger, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{
+    map, testutils::Address as _, testutils::Events as _, testutils::Ledger, vec, xdr::ToXdr,
+    Address, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
+};
 
 fn create_rwa_oracle_contract<'a>(e: &Env) -> RWAOracleClient<'a> {
     set_ledger_timestamp(e, 2_000_000_000);
@@ -269,6 +273,48 @@ fn test_price_feed_compatibility() {
     assert_eq!(prices.get(0).unwrap().price, price2);
 }
 
+#[test]
+fn test_prices_capped_at_max_prices_query() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    assert_eq!(oracle.max_prices_query(), 200);
+
+    oracle.set_max_prices_query(&5);
+    assert_eq!(oracle.max_prices_query(), 5);
+
+    for i in 0..10 {
+        oracle.set_asset_price(&asset, &(100_000 + i as i128), &(1000 + i as u64));
+    }
+
+    // Requests above the cap return the capped count, newest first
+    let prices = oracle.prices(&asset, &8).unwrap();
+    assert_eq!(prices.len(), 5);
+    assert_eq!(prices.get(0).unwrap().price, 100_009);
+
+    let prices = oracle.prices(&asset, &1_000_000).unwrap();
+    assert_eq!(prices.len(), 5);
+}
+
+#[test]
+fn test_prices_under_cap_returns_exact_count() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    oracle.set_max_prices_query(&5);
+
+    for i in 0..10 {
+        oracle.set_asset_price(&asset, &(100_000 + i as i128), &(1000 + i as u64));
+    }
+
+    let prices = oracle.prices(&asset, &3).unwrap();
+    assert_eq!(prices.len(), 3);
+}
+
 // ==================== Error Handling Tests ====================
 
 #[test]
@@ -281,7 +327,7 @@ fn test_error_handling() {
 
     let result = oracle.try_get_rwa_metadata(&non_existent);
     assert!(result.is_err());
-    assert_eq!(result.unwrap_err().unwrap(), Error::AssetNotFound.into());
+    assert_eq!(result.unwrap_err().unwrap(), Error::AssetNotFound);
 }
 
 // ==================== Price History Pruning Tests ====================
@@ -326,6 +372,7 @@ fn test_history_under_limit_not_pruned() {
     e.mock_all_auths();
 
     let oracle = create_rwa_oracle_contract(&e);
+    oracle.set_max_prices_query(&MAX_PRICE_HISTORY);
     let asset = Asset::Other(Symbol::new(&e, "TSLA"));
 
     for i in 0..500 {
@@ -359,6 +406,7 @@ fn test_pruning_per_asset_independent() {
     e.mock_all_auths();
 
     let oracle = create_rwa_oracle_contract(&e);
+    oracle.set_max_prices_query(&MAX_PRICE_HISTORY);
     let asset_nvda = Asset::Other(Symbol::new(&e, "NVDA"));
     let asset_tsla = Asset::Other(Symbol::new(&e, "TSLA"));
 
@@ -486,6 +534,39 @@ fn test_timestamp_within_drift_accepted() {
     assert_eq!(last_price.timestamp, timestamp);
 }
 
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_small_future_drift_rejects_timestamp() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset: Asset = Asset::Other(Symbol::new(&e, "NVDA"));
+
+    oracle.set_max_future_drift(&60);
+    assert_eq!(oracle.max_future_drift(), 60);
+
+    set_ledger_timestamp(&e, 1000);
+    oracle.set_asset_price(&asset, &123, &1200);
+}
+
+#[test]
+fn test_large_future_drift_accepts_timestamp() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset: Asset = Asset::Other(Symbol::new(&e, "NVDA"));
+
+    oracle.set_max_future_drift(&3600);
+
+    set_ledger_timestamp(&e, 1000);
+    oracle.set_asset_price(&asset, &123, &2000);
+
+    let last_price = oracle.lastprice(&asset).unwrap();
+    assert_eq!(last_price.timestamp, 2000);
+}
+
 #[test]
 #[should_panic(expected = "Error(Contract, #8)")]
 fn test_old_timestamp_rejected() {
@@ -623,3 +704,521 @@ fn test_ttl_extended_on_add_assets() {
 
     assert!(oracle.assets().contains(&new_asset));
 }
+
+// ==================== Multi-Source Median Tests ====================
+
+#[test]
+fn test_median_price_three_sources() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    let now = e.ledger().timestamp();
+
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_a"), &asset, &101_000_000, &now);
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_b"), &asset, &99_000_000, &now);
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_c"), &asset, &250_000_000, &now);
+
+    let median = oracle.median_price(&asset);
+    assert_eq!(median.price, 101_000_000);
+    assert_eq!(median.timestamp, now);
+}
+
+#[test]
+fn test_median_price_ignores_stale_source() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    oracle.set_max_staleness(&300);
+
+    let start = e.ledger().timestamp();
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_a"), &asset, &500_000_000, &start);
+
+    set_ledger_timestamp(&e, start + 1_000);
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_b"), &asset, &100_000_000, &now);
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_c"), &asset, &110_000_000, &now);
+
+    // src_a is stale, so the median is taken over src_b and src_c only
+    let median = oracle.median_price(&asset);
+    assert_eq!(median.price, 105_000_000);
+    assert_eq!(median.timestamp, now);
+}
+
+#[test]
+fn test_median_price_too_few_sources() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    oracle.set_max_staleness(&300);
+    oracle.set_min_sources(&2);
+    assert_eq!(oracle.min_sources(), 2);
+
+    let start = e.ledger().timestamp();
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_a"), &asset, &100_000_000, &start);
+
+    set_ledger_timestamp(&e, start + 1_000);
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price_from(&Symbol::new(&e, "src_b"), &asset, &105_000_000, &now);
+
+    let result = oracle.try_median_price(&asset);
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        Error::InsufficientSources
+    );
+
+    // No sources at all
+    let other = Asset::Other(Symbol::new(&e, "TSLA"));
+    let result = oracle.try_median_price(&other);
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        Error::InsufficientSources
+    );
+}
+
+#[test]
+fn test_source_prices_flag_lagging_source() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    let src_a = Symbol::new(&e, "src_a");
+    let src_b = Symbol::new(&e, "src_b");
+    let src_c = Symbol::new(&e, "src_c");
+    oracle.set_max_staleness(&300);
+
+    let start = e.ledger().timestamp();
+    oracle.set_asset_price_from(&src_a, &asset, &500_000_000, &start);
+
+    set_ledger_timestamp(&e, start + 1_000);
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price_from(&src_b, &asset, &100_000_000, &now);
+    oracle.set_asset_price_from(&src_c, &asset, &110_000_000, &now);
+
+    let prices = oracle.get_source_prices(&asset);
+    assert_eq!(prices.len(), 3);
+    for (source_id, data) in prices.iter() {
+        if source_id == src_a {
+            assert_eq!((data.price, data.timestamp), (500_000_000, start));
+        } else if source_id == src_b {
+            assert_eq!((data.price, data.timestamp), (100_000_000, now));
+        } else {
+            assert_eq!(source_id, src_c);
+            assert_eq!((data.price, data.timestamp), (110_000_000, now));
+        }
+    }
+
+    // Only the lagging source is flagged, and the median ignores it
+    assert_eq!(oracle.get_stale_sources(&asset), Vec::from_array(&e, [src_a]));
+    assert_eq!(oracle.median_price(&asset).price, 105_000_000);
+}
+
+#[test]
+fn test_max_sources_caps_new_sources() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let asset = Asset::Other(Symbol::new(&e, "NVDA"));
+    let src_a = Symbol::new(&e, "src_a");
+    let src_b = Symbol::new(&e, "src_b");
+    assert_eq!(oracle.max_sources(), 0);
+    oracle.set_max_sources(&2);
+    assert_eq!(oracle.max_sources(), 2);
+
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price_from(&src_a, &asset, &100_000_000, &now);
+    oracle.set_asset_price_from(&src_b, &asset, &101_000_000, &now);
+
+    let result = oracle.try_set_asset_price_from(&Symbol::new(&e, "src_c"), &asset, &102_000_000, &now);
+    assert_eq!(result.unwrap_err().unwrap(), Error::TooManySources.into());
+
+    // Existing sources can keep reporting
+    oracle.set_asset_price_from(&src_a, &asset, &103_000_000, &(now + 1));
+    assert_eq!(oracle.get_source_prices(&asset).len(), 2);
+}
+
+// ==================== Emergency Freeze Tests ====================
+
+#[test]
+fn test_freeze_asset_blocks_writes_only_for_that_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let tsla = Asset::Other(Symbol::new(&e, "TSLA"));
+    let now = e.ledger().timestamp();
+
+    oracle.set_asset_price(&nvda, &100, &now);
+    oracle.freeze_asset(&nvda);
+    assert!(oracle.is_asset_frozen(&nvda));
+    assert!(!oracle.is_asset_frozen(&tsla));
+
+    // Writes to the frozen asset fail, other assets update normally
+    let result = oracle.try_set_asset_price(&nvda, &110, &(now + 1));
+    assert_eq!(result.unwrap_err().unwrap(), Error::OraclePaused.into());
+    oracle.set_asset_price(&tsla, &200, &(now + 1));
+    assert_eq!(oracle.lastprice(&tsla).unwrap().price, 200);
+
+    // Reads return the last good price by default
+    assert_eq!(oracle.lastprice(&nvda).unwrap().price, 100);
+
+    oracle.unfreeze_asset(&nvda);
+    assert!(!oracle.is_asset_frozen(&nvda));
+    oracle.set_asset_price(&nvda, &110, &(now + 2));
+    assert_eq!(oracle.lastprice(&nvda).unwrap().price, 110);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_frozen_asset_reads_fail_when_configured() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let now = e.ledger().timestamp();
+
+    oracle.set_asset_price(&nvda, &100, &now);
+    oracle.set_frozen_reads_fail(&true);
+    assert!(oracle.frozen_reads_fail());
+    oracle.freeze_asset(&nvda);
+
+    oracle.lastprice(&nvda);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_freeze_unknown_asset_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    oracle.freeze_asset(&Asset::Other(Symbol::new(&e, "AAPL")));
+}
+
+// ==================== Staleness Grace Tests ====================
+
+#[test]
+fn test_lastprice_checked_fresh_is_clean() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    oracle.set_max_staleness(&300);
+    oracle.set_stale_grace_seconds(&nvda, &600);
+
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price(&nvda, &100, &now);
+    set_ledger_timestamp(&e, now + 300);
+
+    let checked = oracle.lastprice_checked(&nvda).unwrap();
+    assert_eq!(checked.price, 100);
+    assert_eq!(checked.timestamp, now);
+    assert!(!checked.degraded);
+}
+
+#[test]
+fn test_lastprice_checked_within_grace_is_degraded() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let tsla = Asset::Other(Symbol::new(&e, "TSLA"));
+    oracle.set_max_staleness(&300);
+    oracle.set_stale_grace_seconds(&nvda, &600);
+    assert_eq!(oracle.stale_grace_seconds(&nvda), 600);
+    assert_eq!(oracle.stale_grace_seconds(&tsla), 0);
+
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price(&nvda, &100, &now);
+    oracle.set_asset_price(&tsla, &200, &now);
+    set_ledger_timestamp(&e, now + 900);
+
+    let checked = oracle.lastprice_checked(&nvda).unwrap();
+    assert_eq!(checked.price, 100);
+    assert!(checked.degraded);
+
+    // Assets without a grace period halt as soon as they go stale
+    assert!(oracle.lastprice_checked(&tsla).is_none());
+}
+
+#[test]
+fn test_lastprice_checked_beyond_grace_is_none() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    oracle.set_max_staleness(&300);
+    oracle.set_stale_grace_seconds(&nvda, &600);
+
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price(&nvda, &100, &now);
+    set_ledger_timestamp(&e, now + 901);
+
+    assert!(oracle.lastprice_checked(&nvda).is_none());
+    // The raw SEP-40 read is unaffected
+    assert_eq!(oracle.lastprice(&nvda).unwrap().price, 100);
+}
+
+// ==================== Batch Update Tests ====================
+
+#[test]
+fn test_set_asset_prices_emits_batch_event() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let tsla = Asset::Other(Symbol::new(&e, "TSLA"));
+    let now = e.ledger().timestamp();
+
+    let prices = Vec::from_array(&e, [(nvda.clone(), 100), (tsla.clone(), 200)]);
+    oracle.set_asset_prices(&prices, &now);
+
+    let assets = Vec::from_array(&e, [nvda.clone(), tsla.clone()]);
+    let assets_hash: BytesN<32> = e.crypto().sha256(&assets.to_xdr(&e)).into();
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            price_updated_event(&e, &oracle.address, &nvda, 100, now),
+            price_updated_event(&e, &oracle.address, &tsla, 200, now),
+            batch_updated_event(&e, &oracle.address, 2, assets_hash, now),
+        ]
+    );
+
+    assert_eq!(oracle.lastprice(&nvda).unwrap().price, 100);
+    assert_eq!(oracle.lastprice(&tsla).unwrap().price, 200);
+}
+
+#[test]
+fn test_set_asset_prices_batch_events_only() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let tsla = Asset::Other(Symbol::new(&e, "TSLA"));
+    let now = e.ledger().timestamp();
+
+    assert!(!oracle.batch_events_only());
+    oracle.set_batch_events_only(&true);
+    assert!(oracle.batch_events_only());
+
+    let prices = Vec::from_array(&e, [(nvda.clone(), 100), (tsla.clone(), 200)]);
+    oracle.set_asset_prices(&prices, &now);
+
+    // Only the batch summary is emitted
+    let assets = Vec::from_array(&e, [nvda, tsla]);
+    let assets_hash: BytesN<32> = e.crypto().sha256(&assets.to_xdr(&e)).into();
+    assert_eq!(
+        e.events().all(),
+        vec![&e, batch_updated_event(&e, &oracle.address, 2, assets_hash, now)]
+    );
+}
+
+/// Expected `PriceUpdated` event as recorded by the host
+fn price_updated_event(
+    e: &Env,
+    oracle: &Address,
+    asset: &Asset,
+    price: i128,
+    timestamp: u64,
+) -> (Address, Vec<Val>, Val) {
+    let data: Map<Symbol, Val> = map![
+        e,
+        (Symbol::new(e, "price"), price.into_val(e)),
+        (Symbol::new(e, "timestamp"), timestamp.into_val(e)),
+    ];
+    (
+        oracle.clone(),
+        (Symbol::new(e, "price_updated"), asset.clone()).into_val(e),
+        data.into_val(e),
+    )
+}
+
+/// Expected `PricesBatchUpdated` event as recorded by the host
+fn batch_updated_event(
+    e: &Env,
+    oracle: &Address,
+    count: u32,
+    assets_hash: BytesN<32>,
+    timestamp: u64,
+) -> (Address, Vec<Val>, Val) {
+    let data: Map<Symbol, Val> = map![
+        e,
+        (Symbol::new(e, "assets_hash"), assets_hash.into_val(e)),
+        (Symbol::new(e, "count"), count.into_val(e)),
+        (Symbol::new(e, "timestamp"), timestamp.into_val(e)),
+    ];
+    (
+        oracle.clone(),
+        (Symbol::new(e, "prices_batch_updated"),).into_val(e),
+        data.into_val(e),
+    )
+}
+
+// ==================== Health Tests ====================
+
+#[test]
+fn test_seconds_since_update_grows_with_time() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+
+    let now = e.ledger().timestamp();
+    oracle.set_asset_price(&nvda, &100, &now);
+    assert_eq!(oracle.last_update_timestamp(&nvda), Some(now));
+    assert_eq!(oracle.seconds_since_update(&nvda), Some(0));
+
+    set_ledger_timestamp(&e, now + 120);
+    assert_eq!(oracle.seconds_since_update(&nvda), Some(120));
+
+    set_ledger_timestamp(&e, now + 500);
+    assert_eq!(oracle.seconds_since_update(&nvda), Some(500));
+
+    // A new sample resets the age
+    oracle.set_asset_price(&nvda, &101, &(now + 450));
+    assert_eq!(oracle.last_update_timestamp(&nvda), Some(now + 450));
+    assert_eq!(oracle.seconds_since_update(&nvda), Some(50));
+}
+
+#[test]
+fn test_update_age_none_for_never_updated_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let tsla = Asset::Other(Symbol::new(&e, "TSLA"));
+    let unknown = Asset::Other(Symbol::new(&e, "AAPL"));
+
+    assert_eq!(oracle.last_update_timestamp(&tsla), None);
+    assert_eq!(oracle.seconds_since_update(&tsla), None);
+    assert_eq!(oracle.seconds_since_update(&unknown), None);
+}
+
+// ==================== Price Normalization Tests ====================
+
+#[test]
+fn test_round_price_floor_and_nearest() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    // Oracle uses 14 decimals; scaling to 7 divides by 10^7
+    let oracle = create_rwa_oracle_contract(&e);
+
+    // Exactly half a unit: floor drops it, nearest rounds up
+    let half = 123_456_785_000_000i128;
+    assert_eq!(oracle.round_price(&half, &7, &RoundingMode::Floor), 12_345_678);
+    assert_eq!(oracle.round_price(&half, &7, &RoundingMode::Nearest), 12_345_679);
+    assert_eq!(oracle.round_price(&half, &7, &RoundingMode::Ceil), 12_345_679);
+
+    // Just below half: both modes agree
+    let below_half = 123_456_784_999_999i128;
+    assert_eq!(oracle.round_price(&below_half, &7, &RoundingMode::Floor), 12_345_678);
+    assert_eq!(oracle.round_price(&below_half, &7, &RoundingMode::Nearest), 12_345_678);
+
+    // Scaling up is exact regardless of mode
+    assert_eq!(oracle.round_price(&half, &18, &RoundingMode::Floor), half * 10_000);
+    assert_eq!(oracle.round_price(&half, &18, &RoundingMode::Nearest), half * 10_000);
+    assert_eq!(oracle.round_price(&half, &14, &RoundingMode::Nearest), half);
+}
+
+#[test]
+fn test_normalized_price_matches_consumer_rounding() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let tsla = Asset::Other(Symbol::new(&e, "TSLA"));
+
+    assert_eq!(oracle.rounding_mode(), RoundingMode::Floor);
+
+    // Raw 14-decimal prices and their expected 7-decimal values as
+    // (floor, nearest, ceil), worked out by hand from the dropped remainder
+    let cases = [
+        (123_456_780_000_000i128, (12_345_678, 12_345_678, 12_345_678)), // exact
+        (123_456_781_234_567, (12_345_678, 12_345_678, 12_345_679)),     // .1234567
+        (123_456_784_999_999, (12_345_678, 12_345_678, 12_345_679)),     // just below half
+        (123_456_785_000_000, (12_345_678, 12_345_679, 12_345_679)),     // exactly half
+        (123_456_786_543_210, (12_345_678, 12_345_679, 12_345_679)),     // .6543210
+        (123_456_789_999_999, (12_345_678, 12_345_679, 12_345_679)),     // just below next unit
+    ];
+
+    let mut now = e.ledger().timestamp();
+    for (raw, (floor, nearest, ceil)) in cases {
+        now += 1;
+        oracle.set_asset_price(&nvda, &raw, &now);
+
+        for (mode, expected) in [
+            (RoundingMode::Floor, floor),
+            (RoundingMode::Nearest, nearest),
+            (RoundingMode::Ceil, ceil),
+        ] {
+            oracle.set_rounding_mode(&mode);
+            assert_eq!(oracle.normalized_price(&nvda, &7), Some(expected));
+            assert_eq!(oracle.round_price(&raw, &7, &mode), expected);
+        }
+    }
+
+    // No price yet
+    assert_eq!(oracle.normalized_price(&tsla, &7), None);
+}
+
+// ==================== Price Confidence Tests ====================
+
+#[test]
+fn test_lastprice_if_confident_filters_low_confidence() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let now = e.ledger().timestamp();
+
+    // High-confidence primary feed
+    oracle.set_asset_price_with_confidence(&nvda, &100, &(now - 20), &9_500);
+    assert_eq!(oracle.price_confidence(&nvda), Some(9_500));
+    let confident = oracle.lastprice_if_confident(&nvda, &9_000).unwrap();
+    assert_eq!(confident.price, 100);
+
+    // Low-confidence backup-derived price replaces it
+    oracle.set_asset_price_with_confidence(&nvda, &90, &(now - 10), &5_000);
+    assert_eq!(oracle.price_confidence(&nvda), Some(5_000));
+    assert!(oracle.lastprice_if_confident(&nvda, &9_000).is_none());
+    assert_eq!(oracle.lastprice_if_confident(&nvda, &5_000).unwrap().price, 90);
+
+    // A plain update carries full confidence
+    oracle.set_asset_price(&nvda, &101, &now);
+    assert_eq!(oracle.price_confidence(&nvda), Some(10_000));
+    assert_eq!(oracle.lastprice_if_confident(&nvda, &9_000).unwrap().price, 101);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_confidence_above_max_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let oracle = create_rwa_oracle_contract(&e);
+    let nvda = Asset::Other(Symbol::new(&e, "NVDA"));
+    let now = e.ledger().timestamp();
+
+    oracle.set_asset_price_with_confidence(&nvda, &100, &now, &10_001);
+}
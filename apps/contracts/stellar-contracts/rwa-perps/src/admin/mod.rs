@@ -1,9 +1,10 @@
-use soroban_sdk::{panic_with_error, Address, Env};
+use soroban_sdk::{Address, Env, Map, Symbol, Vec, panic_with_error};
 
 use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::common::storage::Storage;
-use crate::common::types::{BASIS_POINTS, MarketConfig, PerpsStorage};
+use crate::common::types::{BASIS_POINTS, FundingIndex, MarketConfig, PerpsStorage};
+use crate::operations::funding::Funding;
 
 /// Administrative functions for the perpetuals contract
 pub struct Admin;
@@ -47,6 +48,10 @@ impl Admin {
             protocol_paused: false,
             protocol_fee_rate,
             liquidation_fee_rate,
+            open_cooldown_seconds: 0,
+            max_markets: 0,
+            insurance_fund: 0,
+            total_open_notional: 0,
         };
 
         Storage::set(env, &storage);
@@ -136,6 +141,74 @@ impl Admin {
         Storage::set(env, &storage);
     }
 
+    /// Set the position-opening cooldown (admin only)
+    ///
+    /// After fully closing a position, a trader cannot reopen on the same market
+    /// until this many seconds have elapsed. Zero disables the cooldown.
+    pub fn set_open_cooldown_seconds(env: &Env, seconds: u64) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        storage.open_cooldown_seconds = seconds;
+        Storage::set(env, &storage);
+    }
+
+    /// Get the position-opening cooldown in seconds
+    pub fn get_open_cooldown_seconds(env: &Env) -> u64 {
+        let storage = Storage::get(env);
+        storage.open_cooldown_seconds
+    }
+
+    /// Set the maximum number of markets (admin only)
+    ///
+    /// Zero disables the limit. Lowering the limit below the current market
+    /// count only blocks new markets; existing markets can still be reconfigured.
+    pub fn set_max_markets(env: &Env, max_markets: u32) {
+        Self::require_admin(env);
+
+        let mut storage = Storage::get(env);
+        storage.max_markets = max_markets;
+        Storage::set(env, &storage);
+    }
+
+    /// Get the maximum number of markets (0 = unlimited)
+    pub fn get_max_markets(env: &Env) -> u32 {
+        let storage = Storage::get(env);
+        storage.max_markets
+    }
+
+    /// Get the number of configured markets
+    pub fn get_market_count(env: &Env) -> u32 {
+        Storage::get_markets(env).len()
+    }
+
+    /// Register already-configured markets in the markets list (admin only)
+    ///
+    /// Migration path for markets configured before the list existed. Such
+    /// markets already exist, so they are added even past `max_markets`;
+    /// markets already in the list are skipped.
+    ///
+    /// # Returns
+    /// * Number of markets added to the list
+    pub fn register_markets(env: &Env, rwa_tokens: &Vec<Address>) -> u32 {
+        Self::require_admin(env);
+
+        let mut markets = Storage::get_markets(env);
+        let mut added = 0u32;
+        for rwa_token in rwa_tokens.iter() {
+            if Storage::get_market_config(env, &rwa_token).is_none() {
+                panic_with_error!(env, Error::MarketNotFound);
+            }
+            if !markets.contains(&rwa_token) {
+                markets.push_back(rwa_token);
+                added += 1;
+            }
+        }
+        Storage::set_markets(env, &markets);
+
+        added
+    }
+
     /// Update market configuration (admin only)
     ///
     /// Allows admin to update market parameters for an RWA token
@@ -149,6 +222,46 @@ impl Admin {
         if config.maintenance_margin > BASIS_POINTS as u32 {
             panic_with_error!(env, Error::InvalidInput);
         }
+        for (_, tier_margin) in config.initial_margin_tiers.iter() {
+            if tier_margin == 0 || tier_margin > BASIS_POINTS as u32 {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+
+        // Checkpoint funding under the previous rate, or start a fresh index
+        match Storage::get_market_config(env, rwa_token) {
+            Some(existing) => {
+                if let Err(error) = Funding::checkpoint_funding_index(env, rwa_token, &existing) {
+                    panic_with_error!(env, error);
+                }
+
+                // Markets configured before the list existed join it on reconfiguration
+                let mut markets = Storage::get_markets(env);
+                if !markets.contains(rwa_token) {
+                    markets.push_back(rwa_token.clone());
+                    Storage::set_markets(env, &markets);
+                }
+            }
+            None => {
+                // Enforce the market limit when adding a new market
+                let storage = Storage::get(env);
+                let mut markets = Storage::get_markets(env);
+                if storage.max_markets > 0 && markets.len() >= storage.max_markets {
+                    panic_with_error!(env, Error::MarketLimitReached);
+                }
+                markets.push_back(rwa_token.clone());
+                Storage::set_markets(env, &markets);
+
+                Storage::set_funding_index(
+                    env,
+                    rwa_token,
+                    &FundingIndex {
+                        value: 0,
+                        last_update: env.ledger().timestamp(),
+                    },
+                );
+            }
+        }
 
         Storage::set_market_config(env, rwa_token, config);
 
@@ -160,6 +273,213 @@ impl Admin {
         );
     }
 
+    /// Bind a market to its oracle asset symbol (admin only)
+    ///
+    /// All price reads for the market use the RWA Oracle's price for this symbol.
+    ///
+    /// # Arguments
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `asset_id` - Oracle asset symbol (queried as `Asset::Other(asset_id)`)
+    pub fn set_market_oracle_asset(env: &Env, rwa_token: &Address, asset_id: &Symbol) {
+        Self::require_admin(env);
+
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_market_oracle_asset(env, rwa_token, asset_id);
+        Events::market_oracle_asset_set(env, rwa_token, asset_id);
+    }
+
+    /// Get the oracle asset symbol bound to a market
+    pub fn get_market_oracle_asset(env: &Env, rwa_token: &Address) -> Option<Symbol> {
+        Storage::get_market_oracle_asset(env, rwa_token)
+    }
+
+    /// Set the maximum funding charged per settlement for a market (admin only)
+    ///
+    /// Funding owed beyond this share of the position's margin is carried
+    /// forward to the next settlement. Zero disables the cap.
+    ///
+    /// # Arguments
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `max_bps` - Cap in basis points of margin (max 10000 = 100%)
+    pub fn set_max_funding_settle_bps(env: &Env, rwa_token: &Address, max_bps: u32) {
+        Self::require_admin(env);
+
+        if max_bps > BASIS_POINTS as u32 {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_max_funding_per_settlement(env, rwa_token, max_bps);
+    }
+
+    /// Get the maximum funding charged per settlement for a market (0 = uncapped)
+    pub fn get_max_funding_settle_bps(env: &Env, rwa_token: &Address) -> u32 {
+        Storage::get_max_funding_per_settlement(env, rwa_token).unwrap_or(0)
+    }
+
+    /// Set the minimum absolute margin for positions in a market (admin only)
+    ///
+    /// Applied on top of the ratio-based margin requirements so that dust
+    /// positions cannot be opened or margin drained below the floor.
+    /// Zero disables the floor.
+    ///
+    /// # Arguments
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `min_margin` - Floor in margin token units (must be >= 0)
+    pub fn set_min_absolute_margin(env: &Env, rwa_token: &Address, min_margin: i128) {
+        Self::require_admin(env);
+
+        if min_margin < 0 {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_min_absolute_margin(env, rwa_token, min_margin);
+    }
+
+    /// Get the minimum absolute margin for a market (0 = no floor)
+    pub fn get_min_absolute_margin(env: &Env, rwa_token: &Address) -> i128 {
+        Storage::get_min_absolute_margin(env, rwa_token).unwrap_or(0)
+    }
+
+    /// Set the maximum oracle price age for a market (admin only)
+    ///
+    /// Opens are rejected once the bound oracle price is older than this, and
+    /// `check_market_health` deactivates the market until the admin re-enables
+    /// it through `set_market_config`. Zero disables the check.
+    ///
+    /// # Arguments
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `max_age` - Maximum price age in seconds
+    pub fn set_max_price_age(env: &Env, rwa_token: &Address, max_age: u64) {
+        Self::require_admin(env);
+
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_max_price_age(env, rwa_token, max_age);
+    }
+
+    /// Get the maximum oracle price age for a market (0 = unchecked)
+    pub fn get_max_price_age(env: &Env, rwa_token: &Address) -> u64 {
+        Storage::get_max_price_age(env, rwa_token).unwrap_or(0)
+    }
+
+    /// Set how long a position must be open before its trigger order can execute (admin only)
+    ///
+    /// Keeps a pre-set stop-loss from firing on a momentary wick right after
+    /// the position is opened.
+    ///
+    /// # Arguments
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `delay` - Minimum time in position in seconds (0 = immediate)
+    pub fn set_trigger_activation_delay(env: &Env, rwa_token: &Address, delay: u64) {
+        Self::require_admin(env);
+
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_trigger_activation_delay(env, rwa_token, delay);
+    }
+
+    /// Get the trigger activation delay for a market (0 = immediate)
+    pub fn get_trigger_activation_delay(env: &Env, rwa_token: &Address) -> u64 {
+        Storage::get_trigger_activation_delay(env, rwa_token).unwrap_or(0)
+    }
+
+    /// Enable or disable isolated insurance for a market (admin only)
+    ///
+    /// Isolated markets keep their own insurance sub-fund: deposits and
+    /// liquidation penalties go to it, and shortfalls draw from it before
+    /// falling back to the global fund.
+    pub fn set_market_insurance_isolated(env: &Env, rwa_token: &Address, isolated: bool) {
+        Self::require_admin(env);
+
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_insurance_isolated(env, rwa_token, isolated);
+    }
+
+    /// Flag a market as KYC-gated (admin only)
+    ///
+    /// Opening a position on a gated market requires the configured identity
+    /// verifier to report the trader as verified.
+    pub fn set_market_requires_kyc(env: &Env, rwa_token: &Address, requires_kyc: bool) {
+        Self::require_admin(env);
+
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            panic_with_error!(env, Error::MarketNotFound);
+        }
+
+        Storage::set_requires_kyc(env, rwa_token, requires_kyc);
+    }
+
+    /// Get whether a market is KYC-gated
+    pub fn get_market_requires_kyc(env: &Env, rwa_token: &Address) -> bool {
+        Storage::get_requires_kyc(env, rwa_token).unwrap_or(false)
+    }
+
+    /// Rebuild a trader's token index from their actual positions (admin only)
+    ///
+    /// Scans the registered markets plus `markets`, adds index entries for
+    /// positions that are missing from the index and removes entries that have
+    /// no position behind them. Pass markets configured before the markets list
+    /// existed (see `register_markets`) so their positions are scanned too.
+    ///
+    /// # Returns
+    /// * `(added, removed)` - Number of index entries added and removed
+    pub fn reconcile_trader_index(
+        env: &Env,
+        trader: &Address,
+        markets: &Vec<Address>,
+    ) -> (u32, u32) {
+        Self::require_admin(env);
+
+        let mut added = 0u32;
+        let mut removed = 0u32;
+
+        let mut scan = Storage::get_markets(env);
+        for rwa_token in markets.iter() {
+            if !scan.contains(&rwa_token) {
+                scan.push_back(rwa_token);
+            }
+        }
+
+        // Index every scanned market where the trader holds a position
+        let indexed = Storage::get_trader_tokens(env, trader).unwrap_or_else(|| Map::new(env));
+        for rwa_token in scan.iter() {
+            if Storage::get_position(env, trader, &rwa_token).is_some()
+                && !indexed.contains_key(rwa_token.clone())
+            {
+                Storage::add_trader_token(env, trader, &rwa_token);
+                added += 1;
+            }
+        }
+
+        // Drop entries with no position behind them
+        for rwa_token in indexed.keys().iter() {
+            if Storage::get_position(env, trader, &rwa_token).is_none() {
+                Storage::remove_trader_token(env, trader, &rwa_token);
+                removed += 1;
+            }
+        }
+
+        Events::trader_index_reconciled(env, trader, added, removed);
+
+        (added, removed)
+    }
+
     /// Upgrade the contract to a new WASM hash (admin only)
     ///
     /// # Arguments
@@ -182,4 +502,38 @@ impl Admin {
         Storage::set_margin_token(env, token);
         Events::margin_token_set(env, token);
     }
+
+    /// Set identity verifier contract address (admin only)
+    ///
+    /// The verifier must expose `is_verified(trader: Address) -> bool`. It is
+    /// consulted when opening positions on KYC-gated markets.
+    ///
+    /// # Arguments
+    /// * `verifier` - Identity verifier contract address
+    pub fn set_identity_verifier(env: &Env, verifier: &Address) {
+        Self::require_admin(env);
+        Storage::set_identity_verifier(env, verifier);
+    }
+
+    /// Get identity verifier contract address
+    pub fn get_identity_verifier(env: &Env) -> Option<Address> {
+        Storage::get_identity_verifier(env)
+    }
+
+    /// Set or clear the risk module contract (admin only)
+    ///
+    /// The module must expose `on_liquidation(notice: LiquidationNotice)`. It is
+    /// notified after every liquidation; its failures never block the liquidation.
+    ///
+    /// # Arguments
+    /// * `risk_module` - Risk module contract address, or `None` to disable
+    pub fn set_risk_module(env: &Env, risk_module: &Option<Address>) {
+        Self::require_admin(env);
+        Storage::set_risk_module(env, risk_module);
+    }
+
+    /// Get risk module contract address
+    pub fn get_risk_module(env: &Env) -> Option<Address> {
+        Storage::get_risk_module(env)
+    }
 }
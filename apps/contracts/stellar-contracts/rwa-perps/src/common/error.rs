@@ -3,11 +3,15 @@ use soroban_sdk::contracterror;
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     // Position errors
     PositionNotFound = 1,
     PositionAlreadyExists = 2,
     PositionNotLiquidatable = 3,
+    OpenCooldownActive = 4, // Trader closed on this market too recently
+    TriggerNotActive = 5,   // Position opened within the market's trigger activation delay
+    TriggerNotMet = 6,      // Price has not crossed the stop-loss or take-profit
 
     // Liquidation errors
     MarginRatioHealthy = 10,
@@ -18,10 +22,12 @@ pub enum Error {
     // Market errors
     MarketNotFound = 20,
     MarketInactive = 21,
+    MarketLimitReached = 22, // Adding a market would exceed max_markets
 
     // Oracle errors
     OraclePriceNotFound = 30,
     OraclePriceStale = 31,
+    OracleAssetNotSet = 32, // Market has no oracle asset binding
 
     // Arithmetic errors
     ArithmeticError = 40,
@@ -30,6 +36,7 @@ pub enum Error {
 
     // Authorization errors
     Unauthorized = 50,
+    TraderNotVerified = 51, // KYC-gated market and trader not verified
 
     // General errors
     InvalidInput = 60,
@@ -40,16 +47,18 @@ pub enum Error {
 
     // Protocol state errors
     ProtocolPaused = 63,
+    InsufficientProtocolLiquidity = 64, // Contract margin-token balance cannot cover a payout
 
     // Funding errors
     InvalidFundingRate = 70,
     FundingCalculationError = 71,
 
     // Margin management errors
-    MarginRatioBelowMaintenance = 72,  // Margin removal would violate maintenance requirement
-    MarginTokenNotSet = 73,            // Margin token not configured
+    MarginRatioBelowMaintenance = 72, // Margin removal would violate maintenance requirement
+    MarginTokenNotSet = 73,           // Margin token not configured
 
     // Position validation errors
-    ExceedsMaxLeverage = 80,           // Leverage exceeds market maximum
-    InsufficientInitialMargin = 81,    // Margin below initial requirement
+    ExceedsMaxLeverage = 80,        // Leverage exceeds market maximum
+    InsufficientInitialMargin = 81, // Margin below initial requirement
+    SlippageExceeded = 82,          // Close payout below trader's minimum
 }
@@ -1,7 +1,11 @@
 use soroban_sdk::{Address, Env, Symbol, symbol_short};
 
+use crate::common::types::Position;
+
 pub struct Events;
 
+// Events keep their established topic/data layout rather than #[contractevent]
+#[allow(deprecated)]
 impl Events {
     /// Event emitted when a position is checked for liquidation
     pub fn liquidation_check(
@@ -11,34 +15,50 @@ impl Events {
         is_liquidatable: bool,
         margin_ratio: i128,
     ) {
-        let topics = (
-            symbol_short!("liq_check"),
-            position_id,
-            trader,
-        );
-        env.events().publish(topics, (is_liquidatable, margin_ratio));
+        let topics = (symbol_short!("liq_check"), position_id, trader);
+        env.events()
+            .publish(topics, (is_liquidatable, margin_ratio));
     }
 
     /// Event emitted when a position is liquidated
+    ///
+    /// The position is identified by `(trader, rwa_token)` in the topics.
+    ///
+    /// # Event Data
+    /// * `position_size` - Signed size of the liquidated position
+    /// * `liquidation_price` - Price at which the position was liquidated
+    /// * `liquidation_penalty` - Penalty charged on the position value
+    /// * `liquidator_reward` - Amount owed to the liquidator
+    /// * `notional` - Position value at the liquidation price
+    /// * `leverage` - Position leverage (basis points)
+    /// * `margin` - Margin held by the position before liquidation
+    /// * `margin_token` - Margin token of the protocol, if set
+    #[allow(clippy::too_many_arguments)]
     pub fn position_liquidated(
         env: &Env,
-        position_id: &Address,
         trader: &Address,
+        rwa_token: &Address,
         liquidator: &Address,
-        position_size: i128,
+        position: &Position,
         liquidation_price: i128,
         liquidation_penalty: i128,
         liquidator_reward: i128,
+        notional: i128,
+        margin_token: &Option<Address>,
     ) {
-        let topics = (
-            symbol_short!("liquidate"),
-            position_id,
-            trader,
-            liquidator,
-        );
+        let topics = (symbol_short!("liquidate"), trader, rwa_token, liquidator);
         env.events().publish(
             topics,
-            (position_size, liquidation_price, liquidation_penalty, liquidator_reward),
+            (
+                position.size,
+                liquidation_price,
+                liquidation_penalty,
+                liquidator_reward,
+                notional,
+                position.leverage,
+                position.margin,
+                margin_token.clone(),
+            ),
         );
     }
 
@@ -49,39 +69,24 @@ impl Events {
         trader: &Address,
         liquidation_price: i128,
     ) {
-        let topics = (
-            symbol_short!("liq_price"),
-            position_id,
-            trader,
-        );
+        let topics = (symbol_short!("liq_price"), position_id, trader);
         env.events().publish(topics, liquidation_price);
     }
 
     /// Event emitted when contract is initialized
-    pub fn contract_initialized(
-        env: &Env,
-        admin: &Address,
-        oracle: &Address,
-    ) {
+    pub fn contract_initialized(env: &Env, admin: &Address, oracle: &Address) {
         let topics = (symbol_short!("init"), admin);
         env.events().publish(topics, oracle);
     }
 
     /// Event emitted when oracle address is updated
-    pub fn oracle_updated(
-        env: &Env,
-        old_oracle: &Address,
-        new_oracle: &Address,
-    ) {
+    pub fn oracle_updated(env: &Env, old_oracle: &Address, new_oracle: &Address) {
         let topics = (symbol_short!("oracle"), old_oracle);
         env.events().publish(topics, new_oracle);
     }
 
     /// Event emitted when protocol pause state changes
-    pub fn protocol_paused_updated(
-        env: &Env,
-        paused: bool,
-    ) {
+    pub fn protocol_paused_updated(env: &Env, paused: bool) {
         let topics = (symbol_short!("paused"),);
         env.events().publish(topics, paused);
     }
@@ -94,18 +99,65 @@ impl Events {
         maintenance_margin: u32,
     ) {
         let topics = (symbol_short!("mkt_cfg"), rwa_token);
-        env.events().publish(topics, (max_leverage, maintenance_margin));
+        env.events()
+            .publish(topics, (max_leverage, maintenance_margin));
+    }
+
+    /// Event emitted when a market is bound to an oracle asset
+    pub fn market_oracle_asset_set(env: &Env, rwa_token: &Address, asset_id: &Symbol) {
+        let topics = (symbol_short!("mkt_orc"), rwa_token);
+        env.events().publish(topics, asset_id.clone());
     }
 
     /// Event emitted when margin token is configured
-    pub fn margin_token_set(
-        env: &Env,
-        token: &Address,
-    ) {
+    pub fn margin_token_set(env: &Env, token: &Address) {
         let topics = (symbol_short!("mrg_tkn"),);
         env.events().publish(topics, token);
     }
 
+    /// Event emitted when a trader's token index is reconciled
+    pub fn trader_index_reconciled(env: &Env, trader: &Address, added: u32, removed: u32) {
+        let topics = (symbol_short!("idx_recon"), trader);
+        env.events().publish(topics, (added, removed));
+    }
+
+    /// Event emitted when the insurance fund receives a deposit
+    pub fn insurance_deposited(env: &Env, from: &Address, amount: i128, new_balance: i128) {
+        let topics = (symbol_short!("ins_dep"), from);
+        env.events().publish(topics, (amount, new_balance));
+    }
+
+    /// Event emitted when a market's insurance sub-fund receives a deposit
+    pub fn market_insurance_deposited(
+        env: &Env,
+        from: &Address,
+        rwa_token: &Address,
+        amount: i128,
+        new_balance: i128,
+    ) {
+        let topics = (symbol_short!("mins_dep"), from, rwa_token);
+        env.events().publish(topics, (amount, new_balance));
+    }
+
+    /// Event emitted when insurance covers a liquidation shortfall
+    pub fn insurance_shortfall_covered(
+        env: &Env,
+        rwa_token: &Address,
+        from_market: i128,
+        from_global: i128,
+        uncovered: i128,
+    ) {
+        let topics = (symbol_short!("ins_cover"), rwa_token);
+        env.events()
+            .publish(topics, (from_market, from_global, uncovered));
+    }
+
+    /// Event emitted when a market is deactivated because its oracle price went stale
+    pub fn market_auto_paused(env: &Env, rwa_token: &Address, price_age: u64, max_age: u64) {
+        let topics = (symbol_short!("mkt_pause"), rwa_token);
+        env.events().publish(topics, (price_age, max_age));
+    }
+
     /// Event emitted when margin is added to a position
     pub fn margin_added(
         env: &Env,
@@ -128,10 +180,22 @@ impl Events {
         margin_ratio: i128,
     ) {
         let topics = (symbol_short!("mrg_rem"), trader, rwa_token);
-        env.events().publish(topics, (amount, new_total_margin, margin_ratio));
+        env.events()
+            .publish(topics, (amount, new_total_margin, margin_ratio));
     }
 
     /// Event emitted when a position is opened
+    ///
+    /// The position is identified by `(trader, rwa_token)` in the topics.
+    ///
+    /// # Event Data
+    /// * `size` - Signed position size
+    /// * `entry_price` - Price at which the position was opened
+    /// * `margin` - Margin deposited
+    /// * `leverage` - Position leverage (basis points)
+    /// * `notional` - Position value at the entry price
+    /// * `margin_token` - Token the margin was deposited in
+    #[allow(clippy::too_many_arguments)]
     pub fn position_opened(
         env: &Env,
         trader: &Address,
@@ -140,9 +204,21 @@ impl Events {
         entry_price: i128,
         margin: i128,
         leverage: u32,
+        notional: i128,
+        margin_token: &Address,
     ) {
         let topics = (symbol_short!("pos_open"), trader, rwa_token);
-        env.events().publish(topics, (size, entry_price, margin, leverage));
+        env.events().publish(
+            topics,
+            (
+                size,
+                entry_price,
+                margin,
+                leverage,
+                notional,
+                margin_token.clone(),
+            ),
+        );
     }
 
     /// Event emitted when a position is closed (full or partial)
@@ -152,10 +228,16 @@ impl Events {
     /// * `exit_price` - Price at which the position was closed
     /// * `pnl` - Realized profit/loss for the closed portion
     /// * `remaining_size` - Size remaining after close (0 if fully closed)
+    /// * `notional` - Open notional released by the close (at the entry price)
+    /// * `leverage` - Position leverage (basis points)
+    /// * `margin_token` - Margin token of the protocol, if set
+    ///
+    /// The position is identified by `(trader, rwa_token)` in the topics.
     ///
     /// # Note for Indexers
     /// This event is crucial for tracking position P&L and user balances.
     /// Future versions may include protocol fees deducted from the payout.
+    #[allow(clippy::too_many_arguments)]
     pub fn position_closed(
         env: &Env,
         trader: &Address,
@@ -164,9 +246,75 @@ impl Events {
         exit_price: i128,
         pnl: i128,
         remaining_size: i128,
+        notional: i128,
+        leverage: u32,
+        margin_token: &Option<Address>,
     ) {
         let topics = (symbol_short!("pos_close"), trader, rwa_token);
-        env.events().publish(topics, (size_closed, exit_price, pnl, remaining_size));
+        env.events().publish(
+            topics,
+            (
+                size_closed,
+                exit_price,
+                pnl,
+                remaining_size,
+                notional,
+                leverage,
+                margin_token.clone(),
+            ),
+        );
+    }
+
+    /// Event emitted when a trader sets a stop-loss / take-profit on a position
+    pub fn trigger_order_set(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        stop_loss_price: i128,
+        take_profit_price: i128,
+    ) {
+        let topics = (symbol_short!("trg_set"), trader, rwa_token);
+        env.events()
+            .publish(topics, (stop_loss_price, take_profit_price));
+    }
+
+    /// Event emitted when a keeper closes a position through its trigger order
+    pub fn trigger_executed(
+        env: &Env,
+        keeper: &Address,
+        trader: &Address,
+        rwa_token: &Address,
+        trigger_price: i128,
+    ) {
+        let topics = (symbol_short!("trg_exec"), trader, rwa_token);
+        env.events()
+            .publish(topics, (keeper.clone(), trigger_price));
+    }
+
+    /// Event emitted when a close payout is posted as lending collateral
+    pub fn payout_to_collateral(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        lending_contract: &Address,
+        payout: i128,
+    ) {
+        let topics = (symbol_short!("pos_coll"), trader, rwa_token);
+        env.events()
+            .publish(topics, (lending_contract.clone(), payout));
+    }
+
+    /// Event emitted when a position is moved to a new owner
+    pub fn position_transferred(
+        env: &Env,
+        from: &Address,
+        to: &Address,
+        rwa_token: &Address,
+        size: i128,
+        margin: i128,
+    ) {
+        let topics = (symbol_short!("pos_xfer"), from, to, rwa_token);
+        env.events().publish(topics, (size, margin));
     }
 
     /// Event emitted when a position is queried
@@ -1,8 +1,8 @@
-use soroban_sdk::{panic_with_error, Address, Env, Map, Symbol, symbol_short};
-use crate::common::types::{Position, MarketConfig, PerpsStorage, STORAGE, ADMIN_KEY};
 use crate::common::error::Error;
-
-const PRICE_KEY: Symbol = symbol_short!("price");
+use crate::common::types::{
+    ADMIN_KEY, FundingIndex, MarketConfig, PerpsStorage, Position, STORAGE, TriggerOrder,
+};
+use soroban_sdk::{Address, Env, Map, Symbol, Vec, panic_with_error, symbol_short};
 
 pub struct Storage;
 
@@ -82,17 +82,154 @@ impl Storage {
         env.storage().persistent().set(rwa_token, config);
     }
 
-    /// Get current price for an RWA token from oracle
-    /// This is a placeholder - in production, this would call the oracle contract
-    pub fn get_current_price(env: &Env, rwa_token: &Address) -> Option<i128> {
-        let key = (PRICE_KEY, rwa_token.clone());
+    /// Get all configured markets
+    pub fn get_markets(env: &Env) -> Vec<Address> {
+        let key = symbol_short!("markets");
+        env.storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Set the list of configured markets
+    pub fn set_markets(env: &Env, markets: &Vec<Address>) {
+        let key = symbol_short!("markets");
+        env.storage().persistent().set(&key, markets);
+    }
+
+    /// Get the cumulative funding index for a market
+    pub fn get_funding_index(env: &Env, rwa_token: &Address) -> Option<FundingIndex> {
+        let key = (symbol_short!("fund_idx"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set the cumulative funding index for a market
+    pub fn set_funding_index(env: &Env, rwa_token: &Address, index: &FundingIndex) {
+        let key = (symbol_short!("fund_idx"), rwa_token.clone());
+        env.storage().persistent().set(&key, index);
+    }
+
+    /// Get the oracle asset symbol bound to a market
+    pub fn get_market_oracle_asset(env: &Env, rwa_token: &Address) -> Option<Symbol> {
+        let key = (symbol_short!("orc_asset"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Bind a market to an oracle asset symbol
+    pub fn set_market_oracle_asset(env: &Env, rwa_token: &Address, asset_id: &Symbol) {
+        let key = (symbol_short!("orc_asset"), rwa_token.clone());
+        env.storage().persistent().set(&key, asset_id);
+    }
+
+    /// Get the timestamp of a trader's last full close on a market
+    pub fn get_last_close(env: &Env, trader: &Address, rwa_token: &Address) -> Option<u64> {
+        let key = (symbol_short!("last_cls"), trader.clone(), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Record the timestamp of a trader's full close on a market
+    pub fn set_last_close(env: &Env, trader: &Address, rwa_token: &Address, timestamp: u64) {
+        let key = (symbol_short!("last_cls"), trader.clone(), rwa_token.clone());
+        env.storage().persistent().set(&key, &timestamp);
+    }
+
+    /// Get the per-settlement funding cap for a market (basis points of margin)
+    pub fn get_max_funding_per_settlement(env: &Env, rwa_token: &Address) -> Option<u32> {
+        let key = (symbol_short!("fund_cap"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set the per-settlement funding cap for a market (basis points of margin)
+    pub fn set_max_funding_per_settlement(env: &Env, rwa_token: &Address, max_bps: u32) {
+        let key = (symbol_short!("fund_cap"), rwa_token.clone());
+        env.storage().persistent().set(&key, &max_bps);
+    }
+
+    /// Get a market's isolated insurance sub-fund balance
+    pub fn get_market_insurance(env: &Env, rwa_token: &Address) -> Option<i128> {
+        let key = (symbol_short!("mkt_ins"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set a market's isolated insurance sub-fund balance
+    pub fn set_market_insurance(env: &Env, rwa_token: &Address, balance: i128) {
+        let key = (symbol_short!("mkt_ins"), rwa_token.clone());
+        env.storage().persistent().set(&key, &balance);
+    }
+
+    /// Get whether a market uses an isolated insurance sub-fund
+    pub fn get_insurance_isolated(env: &Env, rwa_token: &Address) -> Option<bool> {
+        let key = (symbol_short!("ins_iso"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set whether a market uses an isolated insurance sub-fund
+    pub fn set_insurance_isolated(env: &Env, rwa_token: &Address, isolated: bool) {
+        let key = (symbol_short!("ins_iso"), rwa_token.clone());
+        env.storage().persistent().set(&key, &isolated);
+    }
+
+    /// Get the absolute margin floor for a market (margin token units)
+    pub fn get_min_absolute_margin(env: &Env, rwa_token: &Address) -> Option<i128> {
+        let key = (symbol_short!("min_marg"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set the absolute margin floor for a market (margin token units)
+    pub fn set_min_absolute_margin(env: &Env, rwa_token: &Address, min_margin: i128) {
+        let key = (symbol_short!("min_marg"), rwa_token.clone());
+        env.storage().persistent().set(&key, &min_margin);
+    }
+
+    /// Get the maximum oracle price age (seconds) before a market is auto-paused
+    pub fn get_max_price_age(env: &Env, rwa_token: &Address) -> Option<u64> {
+        let key = (symbol_short!("max_age"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set the maximum oracle price age (seconds) before a market is auto-paused
+    pub fn set_max_price_age(env: &Env, rwa_token: &Address, max_age: u64) {
+        let key = (symbol_short!("max_age"), rwa_token.clone());
+        env.storage().persistent().set(&key, &max_age);
+    }
+
+    /// Get the trigger activation delay (seconds) for a market
+    pub fn get_trigger_activation_delay(env: &Env, rwa_token: &Address) -> Option<u64> {
+        let key = (symbol_short!("trg_delay"), rwa_token.clone());
         env.storage().persistent().get(&key)
     }
 
-    /// Set current price (for testing purposes)
-    pub fn set_current_price(env: &Env, rwa_token: &Address, price: i128) {
-        let key = (PRICE_KEY, rwa_token.clone());
-        env.storage().persistent().set(&key, &price);
+    /// Set the trigger activation delay (seconds) for a market
+    pub fn set_trigger_activation_delay(env: &Env, rwa_token: &Address, delay: u64) {
+        let key = (symbol_short!("trg_delay"), rwa_token.clone());
+        env.storage().persistent().set(&key, &delay);
+    }
+
+    /// Get the trigger order attached to a trader's position
+    pub fn get_trigger_order(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+    ) -> Option<TriggerOrder> {
+        let key = (symbol_short!("trigger"), trader.clone(), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set the trigger order attached to a trader's position
+    pub fn set_trigger_order(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        order: &TriggerOrder,
+    ) {
+        let key = (symbol_short!("trigger"), trader.clone(), rwa_token.clone());
+        env.storage().persistent().set(&key, order);
+    }
+
+    /// Remove the trigger order attached to a trader's position
+    pub fn remove_trigger_order(env: &Env, trader: &Address, rwa_token: &Address) {
+        let key = (symbol_short!("trigger"), trader.clone(), rwa_token.clone());
+        env.storage().persistent().remove(&key);
     }
 
     /// Get margin token address
@@ -107,6 +244,45 @@ impl Storage {
         env.storage().instance().set(&key, token);
     }
 
+    /// Get identity verifier contract address
+    pub fn get_identity_verifier(env: &Env) -> Option<Address> {
+        let key = symbol_short!("id_verif");
+        env.storage().instance().get(&key)
+    }
+
+    /// Set identity verifier contract address (admin only)
+    pub fn set_identity_verifier(env: &Env, verifier: &Address) {
+        let key = symbol_short!("id_verif");
+        env.storage().instance().set(&key, verifier);
+    }
+
+    /// Get risk module contract address
+    pub fn get_risk_module(env: &Env) -> Option<Address> {
+        let key = symbol_short!("risk_mod");
+        env.storage().instance().get(&key)
+    }
+
+    /// Set or clear risk module contract address (admin only)
+    pub fn set_risk_module(env: &Env, risk_module: &Option<Address>) {
+        let key = symbol_short!("risk_mod");
+        match risk_module {
+            Some(risk_module) => env.storage().instance().set(&key, risk_module),
+            None => env.storage().instance().remove(&key),
+        }
+    }
+
+    /// Get whether a market only accepts verified traders
+    pub fn get_requires_kyc(env: &Env, rwa_token: &Address) -> Option<bool> {
+        let key = (symbol_short!("kyc"), rwa_token.clone());
+        env.storage().persistent().get(&key)
+    }
+
+    /// Set whether a market only accepts verified traders
+    pub fn set_requires_kyc(env: &Env, rwa_token: &Address, requires_kyc: bool) {
+        let key = (symbol_short!("kyc"), rwa_token.clone());
+        env.storage().persistent().set(&key, &requires_kyc);
+    }
+
     /// Get all RWA tokens for which a trader has positions
     pub fn get_trader_tokens(env: &Env, trader: &Address) -> Option<Map<Address, bool>> {
         let key = (symbol_short!("trd_tkns"), trader.clone());
@@ -1,17 +1,44 @@
-use soroban_sdk::{contracttype, Address, Symbol};
+use soroban_sdk::{Address, Map, Symbol, contracttype};
 
 // Position structure
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct Position {
     pub trader: Address,
-    pub rwa_token: Address,      // Address for the RWA stock token
-    pub size: i128,              // Position size (positive = long, negative = short)
-    pub entry_price: i128,       // Average entry price
-    pub margin: i128,            // Collateral amount
-    pub leverage: u32,           // Leverage multiplier (e.g., 5x = 500)
+    pub rwa_token: Address, // Address for the RWA stock token
+    pub size: i128,         // Position size (positive = long, negative = short)
+    pub entry_price: i128,  // Average entry price
+    pub margin: i128,       // Collateral amount
+    pub leverage: u32,      // Leverage multiplier (e.g., 5x = 500)
     pub opened_at: u64,
     pub last_funding_payment: u64,
+    pub entry_funding_index: i128, // Market funding index when funding was last settled
+    pub funding_carry: i128,       // Funding owed but deferred by the per-settlement cap
+}
+
+// Liquidation details passed to the risk module's `on_liquidation` callback
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiquidationNotice {
+    pub trader: Address,
+    pub rwa_token: Address,
+    pub liquidator: Address,
+    pub size: i128, // Signed size of the liquidated position
+    pub liquidation_price: i128,
+    pub notional: i128, // Position value at the liquidation price
+    pub leverage: u32,
+    pub margin: i128, // Margin held before liquidation
+    pub liquidation_penalty: i128,
+    pub liquidator_reward: i128,
+    pub margin_token: Option<Address>,
+}
+
+// Stop-loss / take-profit attached to a position
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct TriggerOrder {
+    pub stop_loss_price: i128, // Close when price moves against the position to here (0 = unset)
+    pub take_profit_price: i128, // Close when price moves in favour of the position to here (0 = unset)
 }
 
 // Market configuration
@@ -19,20 +46,29 @@ pub struct Position {
 #[derive(Clone, Debug)]
 pub struct MarketConfig {
     pub rwa_token: Address,
-    pub max_leverage: u32,        // Maximum allowed leverage (e.g., 10x = 1000)
-    pub maintenance_margin: u32,  // Maintenance margin in basis points (e.g., 500 = 5%)
-    pub initial_margin: u32,      // Initial margin in basis points (e.g., 1000 = 10%)
-    pub funding_rate: i128,       // Current funding rate in basis points (can be negative)
+    pub max_leverage: u32,       // Maximum allowed leverage (e.g., 10x = 1000)
+    pub maintenance_margin: u32, // Maintenance margin in basis points (e.g., 500 = 5%)
+    pub initial_margin: u32,     // Initial margin in basis points (e.g., 1000 = 10%)
+    pub initial_margin_tiers: Map<u32, u32>, // Min leverage -> initial margin (bps); empty = flat initial_margin
+    pub funding_rate: i128, // Current funding rate in basis points (can be negative)
     pub last_funding_update: u64,
     pub is_active: bool,
 }
 
+// Cumulative funding index for a market
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct FundingIndex {
+    pub value: i128, // Sum of funding_rate × seconds elapsed (basis points × seconds)
+    pub last_update: u64,
+}
+
 // Funding payment record
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct FundingPayment {
     pub position_id: Address,
-    pub amount: i128,             // Positive = trader pays, negative = trader receives
+    pub amount: i128, // Positive = trader pays, negative = trader receives
     pub timestamp: u64,
 }
 
@@ -54,6 +90,10 @@ pub struct PerpsStorage {
     pub protocol_paused: bool,
     pub protocol_fee_rate: u32,
     pub liquidation_fee_rate: u32,
+    pub open_cooldown_seconds: u64,
+    pub max_markets: u32,
+    pub insurance_fund: i128,
+    pub total_open_notional: i128,
 }
 
 // Constants
@@ -64,4 +104,4 @@ pub const SCALAR_9: i128 = 1_000_000_000; // 9 decimals for precision
 pub use soroban_sdk::symbol_short;
 
 pub const STORAGE: Symbol = symbol_short!("STORAGE");
-pub const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
\ No newline at end of file
+pub const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
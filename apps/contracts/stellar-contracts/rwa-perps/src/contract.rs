@@ -1,12 +1,15 @@
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Vec};
+use soroban_sdk::{Address, BytesN, Env, Symbol, Vec, contract, contractimpl};
 
 use crate::admin::Admin;
 use crate::common::error::Error;
-use crate::common::types::{MarketConfig, Position};
-use crate::operations::liquidation::Liquidations;
+use crate::common::types::{MarketConfig, Position, TriggerOrder};
 use crate::operations::funding::Funding;
+use crate::operations::insurance::Insurance;
+use crate::operations::liquidation::Liquidations;
 use crate::operations::margin::Margins;
+use crate::operations::oracles::Oracles;
 use crate::operations::positions::Positions;
+use crate::operations::triggers::Triggers;
 
 #[contract]
 pub struct RWAPerpsContract;
@@ -23,7 +26,13 @@ impl RWAPerpsContract {
         protocol_fee_rate: u32,
         liquidation_fee_rate: u32,
     ) {
-        Admin::initialize(&env, &admin, &oracle, protocol_fee_rate, liquidation_fee_rate);
+        Admin::initialize(
+            &env,
+            &admin,
+            &oracle,
+            protocol_fee_rate,
+            liquidation_fee_rate,
+        );
     }
 
     // ========== Admin Functions ==========
@@ -63,11 +72,98 @@ impl RWAPerpsContract {
         Admin::set_liquidation_fee_rate(&env, fee_rate);
     }
 
+    /// Set position-opening cooldown after a close, in seconds (admin only)
+    pub fn set_open_cooldown_seconds(env: Env, seconds: u64) {
+        Admin::set_open_cooldown_seconds(&env, seconds);
+    }
+
+    /// Get position-opening cooldown in seconds
+    pub fn get_open_cooldown_seconds(env: Env) -> u64 {
+        Admin::get_open_cooldown_seconds(&env)
+    }
+
+    /// Set the maximum number of markets, 0 = unlimited (admin only)
+    pub fn set_max_markets(env: Env, max_markets: u32) {
+        Admin::set_max_markets(&env, max_markets);
+    }
+
+    /// Get the maximum number of markets
+    pub fn get_max_markets(env: Env) -> u32 {
+        Admin::get_max_markets(&env)
+    }
+
+    /// Get the number of configured markets
+    pub fn get_market_count(env: Env) -> u32 {
+        Admin::get_market_count(&env)
+    }
+
+    /// Register markets configured before the markets list existed (admin only)
+    /// Returns the number of markets added
+    pub fn register_markets(env: Env, rwa_tokens: Vec<Address>) -> u32 {
+        Admin::register_markets(&env, &rwa_tokens)
+    }
+
     /// Set market configuration (admin only)
     pub fn set_market_config(env: Env, rwa_token: Address, config: MarketConfig) {
         Admin::set_market_config(&env, &rwa_token, &config);
     }
 
+    /// Bind a market to its oracle asset symbol (admin only)
+    pub fn set_market_oracle_asset(env: Env, rwa_token: Address, asset_id: Symbol) {
+        Admin::set_market_oracle_asset(&env, &rwa_token, &asset_id);
+    }
+
+    /// Set the max funding charged per settlement in bps of margin (admin only)
+    pub fn set_max_funding_settle_bps(env: Env, rwa_token: Address, max_bps: u32) {
+        Admin::set_max_funding_settle_bps(&env, &rwa_token, max_bps);
+    }
+
+    /// Get the max funding charged per settlement in bps of margin (0 = uncapped)
+    pub fn get_max_funding_settle_bps(env: Env, rwa_token: Address) -> u32 {
+        Admin::get_max_funding_settle_bps(&env, &rwa_token)
+    }
+
+    /// Set the minimum absolute margin for positions in a market (admin only)
+    pub fn set_min_absolute_margin(env: Env, rwa_token: Address, min_margin: i128) {
+        Admin::set_min_absolute_margin(&env, &rwa_token, min_margin);
+    }
+
+    /// Get the minimum absolute margin for a market (0 = no floor)
+    pub fn get_min_absolute_margin(env: Env, rwa_token: Address) -> i128 {
+        Admin::get_min_absolute_margin(&env, &rwa_token)
+    }
+
+    /// Set the max oracle price age before a market is auto-paused (admin only, 0 = unchecked)
+    pub fn set_max_price_age(env: Env, rwa_token: Address, max_age: u64) {
+        Admin::set_max_price_age(&env, &rwa_token, max_age);
+    }
+
+    /// Get the max oracle price age for a market (0 = unchecked)
+    pub fn get_max_price_age(env: Env, rwa_token: Address) -> u64 {
+        Admin::get_max_price_age(&env, &rwa_token)
+    }
+
+    /// Set how long a position must be open before its trigger order can execute (admin only)
+    pub fn set_trigger_activation_delay(env: Env, rwa_token: Address, delay: u64) {
+        Admin::set_trigger_activation_delay(&env, &rwa_token, delay);
+    }
+
+    /// Get the trigger activation delay for a market (0 = immediate)
+    pub fn get_trigger_activation_delay(env: Env, rwa_token: Address) -> u64 {
+        Admin::get_trigger_activation_delay(&env, &rwa_token)
+    }
+
+    /// Get the oracle asset symbol bound to a market
+    pub fn get_market_oracle_asset(env: Env, rwa_token: Address) -> Option<Symbol> {
+        Admin::get_market_oracle_asset(&env, &rwa_token)
+    }
+
+    /// Rebuild a trader's position index from their open positions (admin only)
+    /// Scans registered markets plus `markets`; returns (added, removed) index entries
+    pub fn reconcile_trader_index(env: Env, trader: Address, markets: Vec<Address>) -> (u32, u32) {
+        Admin::reconcile_trader_index(&env, &trader, &markets)
+    }
+
     /// Upgrade contract WASM (admin only)
     pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
         Admin::upgrade(&env, &new_wasm_hash);
@@ -78,14 +174,53 @@ impl RWAPerpsContract {
         Admin::set_margin_token(&env, &token);
     }
 
+    /// Set identity verifier contract used by KYC-gated markets (admin only)
+    pub fn set_identity_verifier(env: Env, verifier: Address) {
+        Admin::set_identity_verifier(&env, &verifier);
+    }
+
+    /// Get identity verifier contract address
+    pub fn get_identity_verifier(env: Env) -> Option<Address> {
+        Admin::get_identity_verifier(&env)
+    }
+
+    /// Set or clear the risk module notified on liquidations (admin only)
+    pub fn set_risk_module(env: Env, risk_module: Option<Address>) {
+        Admin::set_risk_module(&env, &risk_module);
+    }
+
+    /// Get risk module contract address
+    pub fn get_risk_module(env: Env) -> Option<Address> {
+        Admin::get_risk_module(&env)
+    }
+
+    /// Flag a market as KYC-gated (admin only)
+    pub fn set_market_requires_kyc(env: Env, rwa_token: Address, requires_kyc: bool) {
+        Admin::set_market_requires_kyc(&env, &rwa_token, requires_kyc);
+    }
+
+    /// Get whether a market is KYC-gated
+    pub fn get_market_requires_kyc(env: Env, rwa_token: Address) -> bool {
+        Admin::get_market_requires_kyc(&env, &rwa_token)
+    }
+
+    // ========== Market Health Functions ==========
+
+    /// Deactivate a market whose oracle price exceeds its max age (keeper-callable)
+    /// Returns whether the market is still active
+    pub fn check_market_health(env: Env, rwa_token: Address) -> Result<bool, Error> {
+        Oracles::check_market_health(&env, &rwa_token)
+    }
+
+    /// Get the age in seconds of a market's bound oracle price
+    pub fn get_price_age(env: Env, rwa_token: Address) -> Option<u64> {
+        Oracles::get_price_age(&env, &rwa_token)
+    }
+
     // ========== Liquidation Functions ==========
 
     /// Check if a position is liquidatable
-    pub fn check_liquidation(
-        env: Env,
-        trader: Address,
-        rwa_token: Address,
-    ) -> Result<bool, Error> {
+    pub fn check_liquidation(env: Env, trader: Address, rwa_token: Address) -> Result<bool, Error> {
         Liquidations::check_liquidation(&env, &trader, &rwa_token)
     }
 
@@ -111,29 +246,93 @@ impl RWAPerpsContract {
     // ========== Funding Functions ==========
 
     /// Update funding rate for a market (admin only)
-    pub fn update_funding_rate(
-        env: Env,
-        rwa_token: Address,
-        new_rate: i128,
-    ) -> Result<(), Error> {
+    pub fn update_funding_rate(env: Env, rwa_token: Address, new_rate: i128) -> Result<(), Error> {
         Funding::update_funding_rate(&env, &rwa_token, new_rate)
     }
 
     /// Accrue funding for a position
-    pub fn accrue_funding(
+    pub fn accrue_funding(env: Env, trader: Address, rwa_token: Address) -> Result<i128, Error> {
+        Funding::accrue_funding(&env, &trader, &rwa_token)
+    }
+
+    /// Settle a position's funding into its margin (subject to the per-settlement cap)
+    pub fn settle_funding(env: Env, trader: Address, rwa_token: Address) -> Result<i128, Error> {
+        Funding::settle_funding(&env, &trader, &rwa_token)
+    }
+
+    /// Settle funding into margin for several traders on a market (positions absent are skipped)
+    pub fn settle_funding_batch(
         env: Env,
-        trader: Address,
         rwa_token: Address,
-    ) -> Result<i128, Error> {
-        Funding::accrue_funding(&env, &trader, &rwa_token)
+        traders: Vec<Address>,
+    ) -> Result<Vec<Option<i128>>, Error> {
+        Funding::settle_funding_batch(&env, &rwa_token, &traders)
     }
 
     /// Get current funding rate for a market
-    pub fn get_funding_rate(
+    pub fn get_funding_rate(env: Env, rwa_token: Address) -> Result<i128, Error> {
+        Funding::get_funding_rate(&env, &rwa_token)
+    }
+
+    /// Roll a market's cumulative funding index forward to now
+    pub fn update_funding_index(env: Env, rwa_token: Address) -> Result<i128, Error> {
+        Funding::update_funding_index(&env, &rwa_token)
+    }
+
+    /// Get a position with the current funding index and pending funding
+    pub fn get_position_full(
         env: Env,
+        trader: Address,
         rwa_token: Address,
-    ) -> Result<i128, Error> {
-        Funding::get_funding_rate(&env, &rwa_token)
+    ) -> Result<(Position, i128, i128), Error> {
+        Funding::get_position_full(&env, &trader, &rwa_token)
+    }
+
+    // ========== Insurance Fund Functions ==========
+
+    /// Deposit margin tokens into the insurance fund
+    pub fn deposit_insurance(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+        Insurance::deposit(&env, &from, amount)
+    }
+
+    /// Get the insurance fund balance
+    pub fn get_insurance_fund_balance(env: Env) -> i128 {
+        Insurance::get_balance(&env)
+    }
+
+    /// Enable or disable an isolated insurance sub-fund for a market (admin only)
+    pub fn set_market_insurance_isolated(env: Env, rwa_token: Address, isolated: bool) {
+        Admin::set_market_insurance_isolated(&env, &rwa_token, isolated);
+    }
+
+    /// Whether a market uses an isolated insurance sub-fund
+    pub fn is_market_insurance_isolated(env: Env, rwa_token: Address) -> bool {
+        Insurance::is_isolated(&env, &rwa_token)
+    }
+
+    /// Deposit into a market's insurance (its sub-fund when isolated, else the global fund)
+    pub fn deposit_market_insurance(
+        env: Env,
+        from: Address,
+        rwa_token: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        Insurance::deposit_to_market(&env, &from, &rwa_token, amount)
+    }
+
+    /// Get a market's isolated insurance sub-fund balance
+    pub fn get_market_insurance(env: Env, rwa_token: Address) -> i128 {
+        Insurance::get_market_balance(&env, &rwa_token)
+    }
+
+    /// Get the total notional of open positions (at entry price)
+    pub fn get_open_notional(env: Env) -> i128 {
+        Insurance::get_open_notional(&env)
+    }
+
+    /// Insurance fund balance × BASIS_POINTS / total open notional
+    pub fn insurance_coverage_ratio(env: Env) -> Result<i128, Error> {
+        Insurance::coverage_ratio(&env)
     }
 
     // ========== Margin Management Functions ==========
@@ -196,24 +395,86 @@ impl RWAPerpsContract {
         trader: Address,
         rwa_token: Address,
         size_to_close: i128,
+        min_payout_out: i128,
     ) -> Result<(), Error> {
-        Positions::close_position(&env, &trader, &rwa_token, size_to_close)
+        Positions::close_position(&env, &trader, &rwa_token, size_to_close, min_payout_out)
     }
 
-    /// Get a specific position for a trader
-    pub fn get_position(
+    /// Close a position and post the payout as collateral in a lending pool
+    pub fn close_to_collateral(
         env: Env,
         trader: Address,
         rwa_token: Address,
-    ) -> Result<Position, Error> {
+        size_to_close: i128,
+        lending_contract: Address,
+    ) -> Result<i128, Error> {
+        Positions::close_to_collateral(&env, &trader, &rwa_token, size_to_close, &lending_contract)
+    }
+
+    /// Move a position to a new owner (both parties must authorize)
+    pub fn transfer_position(
+        env: Env,
+        from: Address,
+        to: Address,
+        rwa_token: Address,
+    ) -> Result<(), Error> {
+        Positions::transfer_position(&env, &from, &to, &rwa_token)
+    }
+
+    /// Get a specific position for a trader
+    pub fn get_position(env: Env, trader: Address, rwa_token: Address) -> Result<Position, Error> {
         Positions::get_position(&env, &trader, &rwa_token)
     }
 
     /// Get all positions for a trader
-    pub fn get_user_positions(
+    pub fn get_user_positions(env: Env, trader: Address) -> Vec<Position> {
+        Positions::get_user_positions(&env, &trader)
+    }
+
+    // ========== Trigger Order Functions ==========
+
+    /// Attach a stop-loss and/or take-profit to a position (0 = unset)
+    pub fn set_trigger_order(
         env: Env,
         trader: Address,
-    ) -> Vec<Position> {
-        Positions::get_user_positions(&env, &trader)
+        rwa_token: Address,
+        stop_loss_price: i128,
+        take_profit_price: i128,
+    ) -> Result<(), Error> {
+        Triggers::set_trigger_order(
+            &env,
+            &trader,
+            &rwa_token,
+            stop_loss_price,
+            take_profit_price,
+        )
+    }
+
+    /// Remove the trigger order from a position
+    pub fn cancel_trigger_order(
+        env: Env,
+        trader: Address,
+        rwa_token: Address,
+    ) -> Result<(), Error> {
+        Triggers::cancel_trigger_order(&env, &trader, &rwa_token)
+    }
+
+    /// Close a position whose trigger order has been reached (any keeper)
+    pub fn execute_trigger_order(
+        env: Env,
+        keeper: Address,
+        trader: Address,
+        rwa_token: Address,
+    ) -> Result<(), Error> {
+        Triggers::execute_trigger_order(&env, &keeper, &trader, &rwa_token)
+    }
+
+    /// Get the trigger order attached to a position
+    pub fn get_trigger_order(
+        env: Env,
+        trader: Address,
+        rwa_token: Address,
+    ) -> Option<TriggerOrder> {
+        Triggers::get_trigger_order(&env, &trader, &rwa_token)
     }
 }
@@ -1,9 +1,14 @@
 #![no_std]
 
 mod admin;
-mod contract;
 mod common;
+mod contract;
 mod operations;
 mod test;
 
 pub use contract::{RWAPerpsContract, RWAPerpsContractClient};
+
+// Import RWA Oracle WASM for reading market prices (SEP-40 interface)
+pub mod rwa_oracle {
+    soroban_sdk::contractimport!(file = "../target/wasm32v1-none/release/rwa_oracle.wasm");
+}
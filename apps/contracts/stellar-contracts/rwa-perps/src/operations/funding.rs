@@ -1,9 +1,9 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{Address, Env, Vec};
 
 use crate::admin::Admin;
 use crate::common::error::Error;
 use crate::common::storage::Storage;
-use crate::common::types::{Position, MarketConfig, FundingPayment, BASIS_POINTS};
+use crate::common::types::{BASIS_POINTS, FundingIndex, FundingPayment, MarketConfig, Position};
 
 /// Funding operations for RWA Perpetuals
 pub struct Funding;
@@ -31,8 +31,11 @@ impl Funding {
         Admin::require_admin(env);
 
         // Get market configuration
-        let mut market_config = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+        let mut market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
+
+        // Roll the funding index forward under the previous rate
+        Self::checkpoint_funding_index(env, rwa_token, &market_config)?;
 
         // Update funding rate and timestamp
         market_config.funding_rate = new_rate;
@@ -46,8 +49,9 @@ impl Funding {
 
     /// Accrue funding for a position
     ///
-    /// Calculates the funding payment for a position based on time elapsed
-    /// since last payment and updates the position's margin accordingly.
+    /// Settles the position's funding into its margin through the same capped
+    /// path as `settle_funding`: inactive market periods accrue nothing and a
+    /// charge beyond the per-settlement cap is carried forward.
     ///
     /// # Arguments
     /// * `env` - The contract environment
@@ -57,41 +61,8 @@ impl Funding {
     /// # Returns
     /// * `Ok(funding_payment)` - The funding payment amount (positive = trader pays)
     /// * `Err(Error)` - Position or market not found, calculation error
-    pub fn accrue_funding(
-        env: &Env,
-        trader: &Address,
-        rwa_token: &Address,
-    ) -> Result<i128, Error> {
-        // Get position and market config
-        let mut position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
-        
-        let market_config = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
-
-        // Calculate funding payment
-        let current_time = env.ledger().timestamp();
-        let funding_payment = Self::calculate_funding_payment(
-            &position,
-            &market_config,
-            current_time,
-        );
-
-        // Update position margin (subtract if positive payment, add if negative)
-        position.margin = position.margin
-            .checked_sub(funding_payment)
-            .ok_or(Error::FundingCalculationError)?;
-
-        // Update last funding payment timestamp
-        position.last_funding_payment = current_time;
-
-        // Save updated position
-        Storage::set_position(env, trader, rwa_token, &position);
-
-        // Optionally store funding payment history
-        Self::store_funding_payment_history(env, trader, rwa_token, funding_payment, current_time);
-
-        Ok(funding_payment)
+    pub fn accrue_funding(env: &Env, trader: &Address, rwa_token: &Address) -> Result<i128, Error> {
+        Self::settle_funding(env, trader, rwa_token)
     }
 
     /// Get current funding rate for a market
@@ -105,254 +76,302 @@ impl Funding {
     /// # Returns
     /// * `Ok(funding_rate)` - Current funding rate in basis points
     /// * `Err(Error)` - Market not found
-    pub fn get_funding_rate(
-        env: &Env,
-        rwa_token: &Address,
-    ) -> Result<i128, Error> {
-        let market_config = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+    pub fn get_funding_rate(env: &Env, rwa_token: &Address) -> Result<i128, Error> {
+        let market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
 
         Ok(market_config.funding_rate)
     }
 
-    /// Calculate funding payment for a position (pure helper function)
+    /// Update the cumulative funding index for a market
     ///
-    /// Calculates the funding payment using the formula:
-    /// funding_payment = position_size * funding_rate * time_elapsed / BASIS_POINTS
+    /// Rolls the market's funding index forward to the current ledger time using
+    /// the current funding rate. Anyone may call this to checkpoint funding.
     ///
     /// # Arguments
-    /// * `position` - Position data
+    /// * `env` - The contract environment
+    /// * `rwa_token` - Address of the RWA token market
+    ///
+    /// # Returns
+    /// * `Ok(index)` - The updated funding index
+    /// * `Err(Error)` - Market not found or calculation error
+    pub fn update_funding_index(env: &Env, rwa_token: &Address) -> Result<i128, Error> {
+        let market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
+
+        Self::checkpoint_funding_index(env, rwa_token, &market_config)
+    }
+
+    /// Get the live funding index for a market
+    ///
+    /// Returns the stored index plus funding accrued at the current rate since
+    /// the last checkpoint, without writing to storage. Inactive markets accrue
+    /// nothing; the index is checkpointed whenever a market is activated or
+    /// deactivated, so funding only covers active trading periods.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `rwa_token` - Address of the RWA token market
     /// * `market_config` - Market configuration with funding rate
-    /// * `current_time` - Current timestamp
     ///
     /// # Returns
-    /// * `i128` - Funding payment amount (positive = trader pays, negative = trader receives)
-    pub fn calculate_funding_payment(
-        position: &Position,
+    /// * `Ok(index)` - Current funding index (basis points × seconds)
+    /// * `Err(Error::FundingCalculationError)` - Overflow in calculation
+    pub fn current_funding_index(
+        env: &Env,
+        rwa_token: &Address,
         market_config: &MarketConfig,
-        current_time: u64,
-    ) -> i128 {
-        // Calculate time elapsed since last funding payment
-        let last_payment_time = if position.last_funding_payment == 0 {
-            position.opened_at
-        } else {
-            position.last_funding_payment
-        };
-
-        let time_elapsed = current_time.saturating_sub(last_payment_time);
+    ) -> Result<i128, Error> {
+        let index = Storage::get_funding_index(env, rwa_token).unwrap_or(FundingIndex {
+            value: 0,
+            last_update: market_config.last_funding_update,
+        });
 
-        // If no time elapsed, no funding payment
-        if time_elapsed == 0 {
-            return 0;
+        if !market_config.is_active {
+            return Ok(index.value);
         }
 
-        // Calculate funding payment: position_size * funding_rate * time_elapsed / BASIS_POINTS
-        // Note: We use time_elapsed directly (in seconds) for simplicity
-        let payment = position.size
-            .saturating_mul(market_config.funding_rate)
-            .saturating_mul(time_elapsed as i128)
-            .saturating_div(BASIS_POINTS);
+        let time_elapsed = env.ledger().timestamp().saturating_sub(index.last_update);
 
-        payment
+        market_config
+            .funding_rate
+            .checked_mul(time_elapsed as i128)
+            .and_then(|accrued| index.value.checked_add(accrued))
+            .ok_or(Error::FundingCalculationError)
     }
 
-    /// Store funding payment in history (optional feature)
+    /// Store the live funding index for a market and return it
+    pub fn checkpoint_funding_index(
+        env: &Env,
+        rwa_token: &Address,
+        market_config: &MarketConfig,
+    ) -> Result<i128, Error> {
+        let value = Self::current_funding_index(env, rwa_token, market_config)?;
+        Storage::set_funding_index(
+            env,
+            rwa_token,
+            &FundingIndex {
+                value,
+                last_update: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(value)
+    }
+
+    /// Get a position together with its live funding context
     ///
-    /// Stores a record of the funding payment for historical tracking.
+    /// Bundles the position, the market's current funding index and the funding
+    /// accrued since the position's entry index in a single read.
     ///
     /// # Arguments
     /// * `env` - The contract environment
     /// * `trader` - Address of the position owner
     /// * `rwa_token` - Address of the RWA token market
-    /// * `amount` - Funding payment amount
-    /// * `timestamp` - Payment timestamp
-    fn store_funding_payment_history(
+    ///
+    /// # Returns
+    /// * `Ok((position, current_funding_index, pending_funding))` - Pending funding
+    ///   is positive when the trader pays
+    /// * `Err(Error)` - Position or market not found, calculation error
+    pub fn get_position_full(
         env: &Env,
         trader: &Address,
         rwa_token: &Address,
-        amount: i128,
-        timestamp: u64,
-    ) {
-        let funding_record = FundingPayment {
-            position_id: trader.clone(),
-            amount,
-            timestamp,
-        };
+    ) -> Result<(Position, i128, i128), Error> {
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
-        // Store with composite key: (trader, rwa_token, timestamp)
-        let key = (trader.clone(), rwa_token.clone(), timestamp);
-        env.storage().persistent().set(&key, &funding_record);
-    }
-}
+        let market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::common::types::{MarketConfig, Position, SCALAR_9};
-    use soroban_sdk::{testutils::Address as _, Address, Env};
-
-    #[test]
-    fn test_calculate_funding_payment_positive_rate_long() {
-        let env = Env::default();
-        
-        let position = Position {
-            trader: Address::generate(&env),
-            rwa_token: Address::generate(&env),
-            size: 1000 * SCALAR_9, // Long position
-            entry_price: 100 * SCALAR_9,
-            margin: 10000 * SCALAR_9,
-            leverage: 1000, // 10x
-            opened_at: 1000,
-            last_funding_payment: 1000,
-        };
-
-        let market_config = MarketConfig {
-            rwa_token: position.rwa_token.clone(),
-            max_leverage: 1000,
-            maintenance_margin: 500,
-            initial_margin: 1000,
-            funding_rate: 100, // 1% (positive)
-            last_funding_update: 1000,
-            is_active: true,
-        };
+        let current_index = Self::current_funding_index(env, rwa_token, &market_config)?;
+        let pending_funding = Self::owed_funding(&position, current_index)?;
 
-        let current_time = 4600; // 1 hour later (3600 seconds)
-        let payment = Funding::calculate_funding_payment(&position, &market_config, current_time);
+        Ok((position, current_index, pending_funding))
+    }
 
-        // Expected: 1000 * SCALAR_9 * 100 * 3600 / BASIS_POINTS
-        // = 1000 * 1_000_000_000 * 100 * 3600 / 10_000
-        // = 36_000_000_000_000
-        let expected = 36_000_000_000_000i128;
-        assert_eq!(payment, expected, "Long position should pay positive funding");
+    /// Funding owed by a position at a given index, including carried funding
+    ///
+    /// # Returns
+    /// * `Ok(i128)` - Owed funding (positive = trader pays, negative = trader receives)
+    /// * `Err(Error::FundingCalculationError)` - Overflow in calculation
+    pub fn owed_funding(position: &Position, current_index: i128) -> Result<i128, Error> {
+        Self::calculate_pending_funding(position, current_index)?
+            .checked_add(position.funding_carry)
+            .ok_or(Error::FundingCalculationError)
     }
 
-    #[test]
-    fn test_calculate_funding_payment_positive_rate_short() {
-        let env = Env::default();
-        
-        let position = Position {
-            trader: Address::generate(&env),
-            rwa_token: Address::generate(&env),
-            size: -1000 * SCALAR_9, // Short position
-            entry_price: 100 * SCALAR_9,
-            margin: 10000 * SCALAR_9,
-            leverage: 1000,
-            opened_at: 1000,
-            last_funding_payment: 1000,
-        };
+    /// Settle a position's funding into its margin
+    ///
+    /// Realizes funding accrued since the position's entry index plus any carried
+    /// funding. Periods during which the market was inactive are skipped. When
+    /// the market has a per-settlement cap, a charge to the trader is limited to
+    /// `max_funding_settle_bps` of margin and the excess is carried forward to
+    /// the next settlement. Funding received by the trader is not capped.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `trader` - Address of the position owner
+    /// * `rwa_token` - Address of the RWA token market
+    ///
+    /// # Returns
+    /// * `Ok(realized)` - Funding realized this settlement (positive = trader pays)
+    /// * `Err(Error)` - Position or market not found, calculation error
+    pub fn settle_funding(env: &Env, trader: &Address, rwa_token: &Address) -> Result<i128, Error> {
+        let mut position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
-        let market_config = MarketConfig {
-            rwa_token: position.rwa_token.clone(),
-            max_leverage: 1000,
-            maintenance_margin: 500,
-            initial_margin: 1000,
-            funding_rate: 100, // 1% (positive)
-            last_funding_update: 1000,
-            is_active: true,
-        };
+        let realized = Self::settle_position_funding(env, rwa_token, &mut position)?;
 
-        let current_time = 4600; // 1 hour later
-        let payment = Funding::calculate_funding_payment(&position, &market_config, current_time);
+        Storage::set_position(env, trader, rwa_token, &position);
 
-        // Expected: -1000 * SCALAR_9 * 100 * 3600 / BASIS_POINTS = negative (short receives)
-        let expected = -36_000_000_000_000i128;
-        assert_eq!(payment, expected, "Short position should receive funding (negative payment)");
+        Ok(realized)
     }
 
-    #[test]
-    fn test_calculate_funding_payment_negative_rate_long() {
-        let env = Env::default();
-        
-        let position = Position {
-            trader: Address::generate(&env),
-            rwa_token: Address::generate(&env),
-            size: 1000 * SCALAR_9, // Long position
-            entry_price: 100 * SCALAR_9,
-            margin: 10000 * SCALAR_9,
-            leverage: 1000,
-            opened_at: 1000,
-            last_funding_payment: 1000,
+    /// Settle funding into a position's margin, subject to the per-settlement cap
+    ///
+    /// Every funding realization goes through here. Updates the position in place
+    /// without writing it to storage; the caller persists or removes it. Any
+    /// charge above the cap is left in `funding_carry`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `position` - Position to settle
+    ///
+    /// # Returns
+    /// * `Ok(realized)` - Funding realized this settlement (positive = trader pays)
+    /// * `Err(Error)` - Market not found, calculation error
+    pub fn settle_position_funding(
+        env: &Env,
+        rwa_token: &Address,
+        position: &mut Position,
+    ) -> Result<i128, Error> {
+        let market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
+
+        // Funding owed = accrued since the entry index + carried from earlier settlements
+        let current_index = Self::current_funding_index(env, rwa_token, &market_config)?;
+        let owed = Self::owed_funding(position, current_index)?;
+
+        // Cap charges at a share of margin, carrying the excess forward
+        let max_bps = Storage::get_max_funding_per_settlement(env, rwa_token).unwrap_or(0);
+        let realized = if owed > 0 && max_bps > 0 {
+            let cap = position
+                .margin
+                .max(0)
+                .checked_mul(max_bps as i128)
+                .and_then(|value| value.checked_div(BASIS_POINTS))
+                .ok_or(Error::FundingCalculationError)?;
+            owed.min(cap)
+        } else {
+            owed
         };
 
-        let market_config = MarketConfig {
-            rwa_token: position.rwa_token.clone(),
-            max_leverage: 1000,
-            maintenance_margin: 500,
-            initial_margin: 1000,
-            funding_rate: -100, // -1% (negative)
-            last_funding_update: 1000,
-            is_active: true,
-        };
+        let current_time = env.ledger().timestamp();
+        position.margin = position
+            .margin
+            .checked_sub(realized)
+            .ok_or(Error::FundingCalculationError)?;
+        position.funding_carry = owed
+            .checked_sub(realized)
+            .ok_or(Error::FundingCalculationError)?;
+        position.entry_funding_index = current_index;
+        position.last_funding_payment = current_time;
 
-        let current_time = 4600; // 1 hour later
-        let payment = Funding::calculate_funding_payment(&position, &market_config, current_time);
+        Self::store_funding_payment_history(
+            env,
+            &position.trader,
+            rwa_token,
+            realized,
+            current_time,
+        );
 
-        // Expected: 1000 * SCALAR_9 * (-100) * 3600 / BASIS_POINTS = negative (long receives)
-        let expected = -36_000_000_000_000i128;
-        assert_eq!(payment, expected, "Long position should receive funding with negative rate");
+        Ok(realized)
     }
 
-    #[test]
-    fn test_calculate_funding_payment_zero_time() {
-        let env = Env::default();
-        
-        let position = Position {
-            trader: Address::generate(&env),
-            rwa_token: Address::generate(&env),
-            size: 1000 * SCALAR_9,
-            entry_price: 100 * SCALAR_9,
-            margin: 10000 * SCALAR_9,
-            leverage: 1000,
-            opened_at: 1000,
-            last_funding_payment: 1000,
-        };
-
-        let market_config = MarketConfig {
-            rwa_token: position.rwa_token.clone(),
-            max_leverage: 1000,
-            maintenance_margin: 500,
-            initial_margin: 1000,
-            funding_rate: 100,
-            last_funding_update: 1000,
-            is_active: true,
-        };
+    /// Settle funding into margin for many positions on one market
+    ///
+    /// Keeper sweep over `settle_funding` (e.g. before a mass liquidation check).
+    /// Traders without a position on the market are skipped.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `traders` - Traders whose positions should be settled
+    ///
+    /// # Returns
+    /// * `Ok(results)` - Funding realized per trader, in input order (`None` = no position)
+    /// * `Err(Error)` - Market not found, calculation error
+    pub fn settle_funding_batch(
+        env: &Env,
+        rwa_token: &Address,
+        traders: &Vec<Address>,
+    ) -> Result<Vec<Option<i128>>, Error> {
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            return Err(Error::MarketNotFound);
+        }
 
-        let current_time = 1000; // Same time as last payment
-        let payment = Funding::calculate_funding_payment(&position, &market_config, current_time);
+        let mut results = Vec::new(env);
+        for trader in traders.iter() {
+            let realized = if Storage::get_position(env, &trader, rwa_token).is_some() {
+                Some(Self::settle_funding(env, &trader, rwa_token)?)
+            } else {
+                None
+            };
+            results.push_back(realized);
+        }
 
-        assert_eq!(payment, 0, "Zero time elapsed should result in zero payment");
+        Ok(results)
     }
 
-    #[test]
-    fn test_calculate_funding_payment_new_position() {
-        let env = Env::default();
-        
-        let position = Position {
-            trader: Address::generate(&env),
-            rwa_token: Address::generate(&env),
-            size: 1000 * SCALAR_9,
-            entry_price: 100 * SCALAR_9,
-            margin: 10000 * SCALAR_9,
-            leverage: 1000,
-            opened_at: 1000,
-            last_funding_payment: 0, // New position
-        };
+    /// Calculate funding accrued since the position's entry index (pure helper function)
+    ///
+    /// pending_funding = position_size * (current_index - entry_funding_index) / BASIS_POINTS
+    ///
+    /// # Arguments
+    /// * `position` - Position data
+    /// * `current_index` - Current market funding index
+    ///
+    /// # Returns
+    /// * `Ok(i128)` - Pending funding (positive = trader pays, negative = trader receives)
+    /// * `Err(Error::FundingCalculationError)` - Overflow in calculation
+    pub fn calculate_pending_funding(
+        position: &Position,
+        current_index: i128,
+    ) -> Result<i128, Error> {
+        current_index
+            .checked_sub(position.entry_funding_index)
+            .and_then(|delta| position.size.checked_mul(delta))
+            .and_then(|value| value.checked_div(BASIS_POINTS))
+            .ok_or(Error::FundingCalculationError)
+    }
 
-        let market_config = MarketConfig {
-            rwa_token: position.rwa_token.clone(),
-            max_leverage: 1000,
-            maintenance_margin: 500,
-            initial_margin: 1000,
-            funding_rate: 100,
-            last_funding_update: 1000,
-            is_active: true,
+    /// Store funding payment in history (optional feature)
+    ///
+    /// Stores a record of the funding payment for historical tracking.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `trader` - Address of the position owner
+    /// * `rwa_token` - Address of the RWA token market
+    /// * `amount` - Funding payment amount
+    /// * `timestamp` - Payment timestamp
+    fn store_funding_payment_history(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        amount: i128,
+        timestamp: u64,
+    ) {
+        let funding_record = FundingPayment {
+            position_id: trader.clone(),
+            amount,
+            timestamp,
         };
 
-        let current_time = 4600; // 1 hour after opening
-        let payment = Funding::calculate_funding_payment(&position, &market_config, current_time);
-
-        // Should use opened_at time since last_funding_payment is 0
-        let expected = 36_000_000_000_000i128;
-        assert_eq!(payment, expected, "New position should use opened_at time");
+        // Store with composite key: (trader, rwa_token, timestamp)
+        let key = (trader.clone(), rwa_token.clone(), timestamp);
+        env.storage().persistent().set(&key, &funding_record);
     }
-}
\ No newline at end of file
+}
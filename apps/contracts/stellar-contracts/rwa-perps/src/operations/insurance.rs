@@ -0,0 +1,207 @@
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::{Address, Env};
+
+use crate::common::error::Error;
+use crate::common::events::Events;
+use crate::common::storage::Storage;
+use crate::common::types::{BASIS_POINTS, SCALAR_9};
+
+/// Insurance fund functions for RWA Perpetuals
+pub struct Insurance;
+
+impl Insurance {
+    /// Deposit margin tokens into the insurance fund
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - Address funding the deposit (must authorize transaction)
+    /// * `amount` - Amount of margin tokens to deposit (must be > 0)
+    ///
+    /// # Errors
+    /// * `InvalidInput` - Amount is <= 0
+    /// * `MarginTokenNotSet` - Margin token not configured
+    /// * `ArithmeticError` - Overflow in calculations
+    pub fn deposit(env: &Env, from: &Address, amount: i128) -> Result<(), Error> {
+        from.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
+        let token_client = TokenClient::new(env, &margin_token);
+        token_client.transfer(from, env.current_contract_address(), &amount);
+
+        let mut storage = Storage::get(env);
+        storage.insurance_fund = storage
+            .insurance_fund
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticError)?;
+        Storage::set(env, &storage);
+
+        Events::insurance_deposited(env, from, amount, storage.insurance_fund);
+
+        Ok(())
+    }
+
+    /// Deposit margin tokens into a market's insurance
+    ///
+    /// Credits the market's isolated sub-fund when the market has isolated
+    /// insurance enabled, otherwise the global fund.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - Amount is <= 0
+    /// * `MarketNotFound` - Market configuration not found
+    /// * `MarginTokenNotSet` - Margin token not configured
+    /// * `ArithmeticError` - Overflow in calculations
+    pub fn deposit_to_market(
+        env: &Env,
+        from: &Address,
+        rwa_token: &Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        if Storage::get_market_config(env, rwa_token).is_none() {
+            return Err(Error::MarketNotFound);
+        }
+        if !Self::is_isolated(env, rwa_token) {
+            return Self::deposit(env, from, amount);
+        }
+
+        from.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
+        let token_client = TokenClient::new(env, &margin_token);
+        token_client.transfer(from, env.current_contract_address(), &amount);
+
+        let new_balance = Self::get_market_balance(env, rwa_token)
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticError)?;
+        Storage::set_market_insurance(env, rwa_token, new_balance);
+
+        Events::market_insurance_deposited(env, from, rwa_token, amount, new_balance);
+
+        Ok(())
+    }
+
+    /// Get the insurance fund balance
+    pub fn get_balance(env: &Env) -> i128 {
+        let storage = Storage::get(env);
+        storage.insurance_fund
+    }
+
+    /// Get a market's isolated insurance sub-fund balance
+    pub fn get_market_balance(env: &Env, rwa_token: &Address) -> i128 {
+        Storage::get_market_insurance(env, rwa_token).unwrap_or(0)
+    }
+
+    /// Whether a market routes insurance through its own sub-fund
+    pub fn is_isolated(env: &Env, rwa_token: &Address) -> bool {
+        Storage::get_insurance_isolated(env, rwa_token).unwrap_or(false)
+    }
+
+    /// Credit a collected liquidation penalty to the market's insurance
+    /// (its sub-fund when isolated, otherwise the global fund)
+    pub fn credit_penalty(env: &Env, rwa_token: &Address, amount: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Ok(());
+        }
+
+        if Self::is_isolated(env, rwa_token) {
+            let new_balance = Self::get_market_balance(env, rwa_token)
+                .checked_add(amount)
+                .ok_or(Error::ArithmeticError)?;
+            Storage::set_market_insurance(env, rwa_token, new_balance);
+        } else {
+            let mut storage = Storage::get(env);
+            storage.insurance_fund = storage
+                .insurance_fund
+                .checked_add(amount)
+                .ok_or(Error::ArithmeticError)?;
+            Storage::set(env, &storage);
+        }
+        Ok(())
+    }
+
+    /// Cover a liquidation shortfall from insurance
+    ///
+    /// Isolated markets draw from their own sub-fund first; whatever remains
+    /// falls back to the global fund as a backstop.
+    ///
+    /// # Returns
+    /// * `Ok(uncovered)` - Part of the shortfall insurance could not cover
+    pub fn cover_shortfall(env: &Env, rwa_token: &Address, shortfall: i128) -> Result<i128, Error> {
+        if shortfall <= 0 {
+            return Ok(0);
+        }
+
+        let mut remaining = shortfall;
+
+        let mut from_market = 0;
+        if Self::is_isolated(env, rwa_token) {
+            let market_balance = Self::get_market_balance(env, rwa_token);
+            from_market = remaining.min(market_balance);
+            Storage::set_market_insurance(env, rwa_token, market_balance - from_market);
+            remaining -= from_market;
+        }
+
+        let mut storage = Storage::get(env);
+        let from_global = remaining.min(storage.insurance_fund);
+        storage.insurance_fund -= from_global;
+        Storage::set(env, &storage);
+        remaining -= from_global;
+
+        Events::insurance_shortfall_covered(env, rwa_token, from_market, from_global, remaining);
+
+        Ok(remaining)
+    }
+
+    /// Get the total notional of open positions (at entry price)
+    pub fn get_open_notional(env: &Env) -> i128 {
+        let storage = Storage::get(env);
+        storage.total_open_notional
+    }
+
+    /// Insurance coverage ratio in basis points
+    ///
+    /// coverage = insurance_fund × BASIS_POINTS / total_open_notional
+    ///
+    /// Returns `i128::MAX` when there is no open notional.
+    pub fn coverage_ratio(env: &Env) -> Result<i128, Error> {
+        let storage = Storage::get(env);
+        if storage.total_open_notional == 0 {
+            return Ok(i128::MAX);
+        }
+
+        storage
+            .insurance_fund
+            .checked_mul(BASIS_POINTS)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(storage.total_open_notional)
+            .ok_or(Error::DivisionByZero)
+    }
+
+    /// Notional of an absolute position size at a price
+    pub fn notional(abs_size: i128, price: i128) -> Result<i128, Error> {
+        abs_size
+            .checked_mul(price)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(SCALAR_9)
+            .ok_or(Error::DivisionByZero)
+    }
+
+    /// Adjust the tracked open notional by a signed delta
+    pub fn adjust_open_notional(env: &Env, delta: i128) -> Result<(), Error> {
+        let mut storage = Storage::get(env);
+        storage.total_open_notional = storage
+            .total_open_notional
+            .checked_add(delta)
+            .ok_or(Error::ArithmeticError)?
+            .max(0);
+        Storage::set(env, &storage);
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{Address, Env, IntoVal, Symbol, vec};
 
 use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::common::storage::Storage;
-use crate::common::types::{Position, BASIS_POINTS, SCALAR_9};
+use crate::common::types::{BASIS_POINTS, LiquidationNotice, Position, SCALAR_9};
+use crate::operations::funding::Funding;
+use crate::operations::insurance::Insurance;
+use crate::operations::oracles::Oracles;
 
 /// Liquidation penalty in basis points (5% = 500 basis points)
 const LIQUIDATION_PENALTY_BP: i128 = 500;
@@ -17,7 +20,9 @@ impl Liquidations {
     /// Evaluates whether a position is undercollateralized by calculating the margin ratio
     /// and comparing it against the maintenance margin threshold.
     ///
-    /// Margin Ratio = (margin + unrealized_pnl) / position_value
+    /// Margin Ratio = (margin + unrealized_pnl - owed_funding) / position_value
+    ///
+    /// Owed funding includes funding carried forward by capped settlements.
     ///
     /// A position is liquidatable if margin_ratio < maintenance_margin
     ///
@@ -36,20 +41,19 @@ impl Liquidations {
         rwa_token: &Address,
     ) -> Result<bool, Error> {
         // Get the position
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
         // Get market configuration for maintenance margin
-        let market_config = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+        let market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
 
         if !market_config.is_active {
             return Err(Error::MarketInactive);
         }
 
         // Get current price from oracle
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        let current_price = Oracles::get_price(env, rwa_token)?;
 
         // Calculate unrealized PnL
         // For long positions (size > 0): PnL = size * (current_price - entry_price)
@@ -60,10 +64,16 @@ impl Liquidations {
         // position_value = abs(size) * current_price / SCALAR_9
         let position_value = Self::calculate_position_value(&position, current_price)?;
 
-        // Calculate margin ratio: (margin + unrealized_pnl) / position_value
+        // Funding the position still owes (positive = trader pays)
+        let current_index = Funding::current_funding_index(env, rwa_token, &market_config)?;
+        let owed_funding = Funding::owed_funding(&position, current_index)?;
+
+        // Calculate margin ratio: (margin + unrealized_pnl - owed_funding) / position_value
         // Both numerator and denominator should be in the same units
-        let effective_margin = position.margin
+        let effective_margin = position
+            .margin
             .checked_add(unrealized_pnl)
+            .and_then(|value| value.checked_sub(owed_funding))
             .ok_or(Error::ArithmeticError)?;
 
         if position_value == 0 {
@@ -90,7 +100,7 @@ impl Liquidations {
     ///
     /// Closes a position that has fallen below the maintenance margin requirement.
     /// The liquidation process:
-    /// 1. Closes the position at current market price
+    /// 1. Realizes owed funding and closes the position at current market price
     /// 2. Applies a liquidation penalty (~5% of position value)
     /// 3. Rewards the liquidator with remaining margin after penalty
     ///
@@ -119,12 +129,14 @@ impl Liquidations {
         }
 
         // Get the position
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let mut position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
+
+        // Settle funding into margin under the per-settlement cap before splitting it up
+        Funding::settle_position_funding(env, rwa_token, &mut position)?;
 
         // Get current price
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        let current_price = Oracles::get_price(env, rwa_token)?;
 
         // Calculate unrealized PnL
         let unrealized_pnl = Self::calculate_unrealized_pnl(&position, current_price)?;
@@ -139,9 +151,11 @@ impl Liquidations {
             .checked_div(BASIS_POINTS)
             .ok_or(Error::DivisionByZero)?;
 
-        // Calculate effective margin after PnL
-        let effective_margin = position.margin
+        // Calculate effective margin after PnL and funding still carried
+        let effective_margin = position
+            .margin
             .checked_add(unrealized_pnl)
+            .and_then(|value| value.checked_sub(position.funding_carry))
             .ok_or(Error::ArithmeticError)?;
 
         // Calculate liquidator reward (remaining margin after penalty)
@@ -152,29 +166,77 @@ impl Liquidations {
             .max(0);
 
         // Emit liquidation event
+        let margin_token = Storage::get_margin_token(env);
         Events::position_liquidated(
             env,
             trader,
-            trader,
+            rwa_token,
             liquidator,
-            position.size,
+            &position,
             current_price,
             liquidation_penalty,
             liquidator_reward,
+            position_value,
+            &margin_token,
         );
 
-        // Remove the position (close it)
+        // Route the collected penalty to insurance and cover any bad debt
+        Insurance::credit_penalty(
+            env,
+            rwa_token,
+            effective_margin.clamp(0, liquidation_penalty),
+        )?;
+        if effective_margin < 0 {
+            Insurance::cover_shortfall(env, rwa_token, -effective_margin)?;
+        }
+
+        // Remove the position (close it) and release its open notional
         Storage::remove_position(env, trader, rwa_token);
+        Storage::remove_trigger_order(env, trader, rwa_token);
+        Insurance::adjust_open_notional(
+            env,
+            -Insurance::notional(position.size.abs(), position.entry_price)?,
+        )?;
+
+        // Let an external risk module react to the liquidation
+        Self::notify_risk_module(
+            env,
+            &LiquidationNotice {
+                trader: trader.clone(),
+                rwa_token: rwa_token.clone(),
+                liquidator: liquidator.clone(),
+                size: position.size,
+                liquidation_price: current_price,
+                notional: position_value,
+                leverage: position.leverage,
+                margin: position.margin,
+                liquidation_penalty,
+                liquidator_reward,
+                margin_token,
+            },
+        );
 
         // In a real implementation, we would:
-        // 1. Transfer liquidation penalty to protocol treasury
-        // 2. Transfer liquidator reward to liquidator
-        // 3. Close the position in the market
-        // 4. Update funding payments
+        // 1. Transfer liquidator reward to liquidator
+        // 2. Close the position in the market
 
         Ok(liquidator_reward)
     }
 
+    /// Call `on_liquidation(notice)` on the configured risk module, if any
+    ///
+    /// Failures in the risk module are ignored so a misbehaving module can
+    /// never block liquidations.
+    fn notify_risk_module(env: &Env, notice: &LiquidationNotice) {
+        if let Some(risk_module) = Storage::get_risk_module(env) {
+            let _ = env.try_invoke_contract::<(), soroban_sdk::Error>(
+                &risk_module,
+                &Symbol::new(env, "on_liquidation"),
+                vec![env, notice.clone().into_val(env)],
+            );
+        }
+    }
+
     /// Calculate the price at which a position would be liquidated
     ///
     /// Uses the formula:
@@ -197,12 +259,12 @@ impl Liquidations {
         rwa_token: &Address,
     ) -> Result<i128, Error> {
         // Get the position
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
         // Get market configuration for maintenance margin
-        let market_config = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+        let market_config =
+            Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
 
         if position.leverage == 0 {
             return Err(Error::DivisionByZero);
@@ -225,7 +287,8 @@ impl Liquidations {
                 .checked_sub(mm_leverage_ratio)
                 .ok_or(Error::ArithmeticError)?;
 
-            position.entry_price
+            position
+                .entry_price
                 .checked_mul(factor)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(BASIS_POINTS)
@@ -236,7 +299,8 @@ impl Liquidations {
                 .checked_add(mm_leverage_ratio)
                 .ok_or(Error::ArithmeticError)?;
 
-            position.entry_price
+            position
+                .entry_price
                 .checked_mul(factor)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(BASIS_POINTS)
@@ -252,7 +316,10 @@ impl Liquidations {
     // Helper functions
 
     /// Calculate unrealized PnL for a position
-    pub fn calculate_unrealized_pnl(position: &Position, current_price: i128) -> Result<i128, Error> {
+    pub fn calculate_unrealized_pnl(
+        position: &Position,
+        current_price: i128,
+    ) -> Result<i128, Error> {
         let price_diff = current_price
             .checked_sub(position.entry_price)
             .ok_or(Error::ArithmeticError)?;
@@ -260,7 +327,8 @@ impl Liquidations {
         // PnL = size * price_diff / SCALAR_9
         // For long (size > 0): positive when price increases
         // For short (size < 0): positive when price decreases
-        let pnl = position.size
+        let pnl = position
+            .size
             .checked_mul(price_diff)
             .ok_or(Error::ArithmeticError)?
             .checked_div(SCALAR_9)
@@ -270,11 +338,12 @@ impl Liquidations {
     }
 
     /// Calculate position value at current price
-    pub fn calculate_position_value(position: &Position, current_price: i128) -> Result<i128, Error> {
+    pub fn calculate_position_value(
+        position: &Position,
+        current_price: i128,
+    ) -> Result<i128, Error> {
         let abs_size = if position.size < 0 {
-            position.size
-                .checked_neg()
-                .ok_or(Error::ArithmeticError)?
+            position.size.checked_neg().ok_or(Error::ArithmeticError)?
         } else {
             position.size
         };
@@ -292,8 +361,8 @@ impl Liquidations {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::types::{MarketConfig, Position};
-    use soroban_sdk::{testutils::Address as _, Address, Env};
+    use crate::common::types::Position;
+    use soroban_sdk::{Address, Env, testutils::Address as _};
 
     // Note: These tests require a full contract implementation to run properly.
     // They are kept here for documentation purposes and should be moved to
@@ -320,6 +389,8 @@ mod tests {
             leverage: 1000,
             opened_at: 0,
             last_funding_payment: 0,
+            entry_funding_index: 0,
+            funding_carry: 0,
         };
 
         let current_price = 110 * SCALAR_9; // 10% price increase (with SCALAR_9)
@@ -330,7 +401,10 @@ mod tests {
         //         = 100_000 * 10 * SCALAR_9
         //         = 1_000_000 * SCALAR_9
         let expected_pnl = 1_000_000 * SCALAR_9;
-        assert_eq!(pnl, expected_pnl, "Long position profit should be 1,000,000 * SCALAR_9");
+        assert_eq!(
+            pnl, expected_pnl,
+            "Long position profit should be 1,000,000 * SCALAR_9"
+        );
     }
 
     #[test]
@@ -345,6 +419,8 @@ mod tests {
             leverage: 1000,
             opened_at: 0,
             last_funding_payment: 0,
+            entry_funding_index: 0,
+            funding_carry: 0,
         };
 
         let current_price = 90 * SCALAR_9; // 10% price decrease
@@ -354,7 +430,10 @@ mod tests {
         //         = (100_000 * SCALAR_9) * (-10 * SCALAR_9) / SCALAR_9
         //         = -1_000_000 * SCALAR_9
         let expected_pnl = -1_000_000 * SCALAR_9;
-        assert_eq!(pnl, expected_pnl, "Long position loss should be -1,000,000 * SCALAR_9");
+        assert_eq!(
+            pnl, expected_pnl,
+            "Long position loss should be -1,000,000 * SCALAR_9"
+        );
     }
 
     #[test]
@@ -369,6 +448,8 @@ mod tests {
             leverage: 1000,
             opened_at: 0,
             last_funding_payment: 0,
+            entry_funding_index: 0,
+            funding_carry: 0,
         };
 
         let current_price = 90 * SCALAR_9; // 10% price decrease (profit for short)
@@ -378,7 +459,10 @@ mod tests {
         //         = (-100_000 * SCALAR_9) * (-10 * SCALAR_9) / SCALAR_9
         //         = 1_000_000 * SCALAR_9
         let expected_pnl = 1_000_000 * SCALAR_9;
-        assert_eq!(pnl, expected_pnl, "Short position profit should be 1,000,000 * SCALAR_9");
+        assert_eq!(
+            pnl, expected_pnl,
+            "Short position profit should be 1,000,000 * SCALAR_9"
+        );
     }
 
     #[test]
@@ -392,6 +476,8 @@ mod tests {
             leverage: 1000,
             opened_at: 0,
             last_funding_payment: 0,
+            entry_funding_index: 0,
+            funding_carry: 0,
         };
 
         let current_price = 110 * SCALAR_9;
@@ -401,7 +487,10 @@ mod tests {
         //         = 100_000 * 110 * SCALAR_9
         //         = 11_000_000 * SCALAR_9
         let expected_value = 11_000_000 * SCALAR_9;
-        assert_eq!(value, expected_value, "Position value should be 11,000,000 * SCALAR_9");
+        assert_eq!(
+            value, expected_value,
+            "Position value should be 11,000,000 * SCALAR_9"
+        );
     }
 
     #[test]
@@ -416,6 +505,8 @@ mod tests {
             leverage: 1000,
             opened_at: 0,
             last_funding_payment: 0,
+            entry_funding_index: 0,
+            funding_carry: 0,
         };
 
         let current_price = 110 * SCALAR_9;
@@ -425,6 +516,9 @@ mod tests {
         //         = (100_000 * SCALAR_9) * (110 * SCALAR_9) / SCALAR_9
         //         = 11_000_000 * SCALAR_9
         let expected_value = 11_000_000 * SCALAR_9;
-        assert_eq!(value, expected_value, "Short position value should be 11,000,000 * SCALAR_9");
+        assert_eq!(
+            value, expected_value,
+            "Short position value should be 11,000,000 * SCALAR_9"
+        );
     }
 }
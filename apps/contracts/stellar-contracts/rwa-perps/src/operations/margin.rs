@@ -1,11 +1,12 @@
-use soroban_sdk::{Address, Env};
 use soroban_sdk::token::TokenClient;
+use soroban_sdk::{Address, Env};
 
 use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::common::storage::Storage;
-use crate::common::types::{BASIS_POINTS, SCALAR_9};
+use crate::common::types::BASIS_POINTS;
 use crate::operations::liquidation::Liquidations;
+use crate::operations::oracles::Oracles;
 
 /// Safety buffer above maintenance margin (0.5% = 50 basis points)
 /// Used in get_available_margin to prevent accidental liquidation
@@ -58,25 +59,24 @@ impl Margins {
         }
 
         // 3. Get position
-        let mut position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let mut position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
         // 4. Validate market
-        let market = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+        let market = Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
         if !market.is_active {
             return Err(Error::MarketInactive);
         }
 
         // 5. Transfer tokens from trader to contract
-        let margin_token = Storage::get_margin_token(env)
-            .ok_or(Error::MarginTokenNotSet)?;
+        let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
         let token_client = TokenClient::new(env, &margin_token);
         let contract_address = env.current_contract_address();
         token_client.transfer(trader, &contract_address, &amount);
 
         // 6. Update position margin
-        position.margin = position.margin
+        position.margin = position
+            .margin
             .checked_add(amount)
             .ok_or(Error::ArithmeticError)?;
         Storage::set_position(env, trader, rwa_token, &position);
@@ -112,6 +112,7 @@ impl Margins {
     /// * `MarketInactive` - Market is not active
     /// * `OraclePriceNotFound` - Cannot fetch current price
     /// * `MarginRatioBelowMaintenance` - Removal would violate margin requirements
+    /// * `InsufficientInitialMargin` - Remaining margin below the market's absolute floor
     /// * `MarginTokenNotSet` - Margin token not configured
     /// * `ArithmeticError` - Overflow in calculations
     /// * `DivisionByZero` - Position value is zero
@@ -135,29 +136,33 @@ impl Margins {
         }
 
         // 3. Get position
-        let mut position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let mut position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
         if amount > position.margin {
             return Err(Error::InsufficientMargin);
         }
 
         // 4. Validate market
-        let market = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+        let market = Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
         if !market.is_active {
             return Err(Error::MarketInactive);
         }
 
         // 5. Get current price
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        let current_price = Oracles::get_price(env, rwa_token)?;
 
         // 6. Calculate post-removal margin ratio
-        let new_margin = position.margin
+        let new_margin = position
+            .margin
             .checked_sub(amount)
             .ok_or(Error::ArithmeticError)?;
 
+        let min_absolute_margin = Storage::get_min_absolute_margin(env, rwa_token).unwrap_or(0);
+        if new_margin < min_absolute_margin {
+            return Err(Error::InsufficientInitialMargin);
+        }
+
         let unrealized_pnl = Liquidations::calculate_unrealized_pnl(&position, current_price)?;
         let position_value = Liquidations::calculate_position_value(&position, current_price)?;
 
@@ -181,8 +186,7 @@ impl Margins {
         }
 
         // 8. Transfer tokens from contract back to trader
-        let margin_token = Storage::get_margin_token(env)
-            .ok_or(Error::MarginTokenNotSet)?;
+        let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
         let token_client = TokenClient::new(env, &margin_token);
         let contract_address = env.current_contract_address();
         token_client.transfer(&contract_address, trader, &amount);
@@ -232,16 +236,16 @@ impl Margins {
         trader: &Address,
         rwa_token: &Address,
     ) -> Result<i128, Error> {
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        let current_price = Oracles::get_price(env, rwa_token)?;
 
         let unrealized_pnl = Liquidations::calculate_unrealized_pnl(&position, current_price)?;
         let position_value = Liquidations::calculate_position_value(&position, current_price)?;
 
-        let effective_margin = position.margin
+        let effective_margin = position
+            .margin
             .checked_add(unrealized_pnl)
             .ok_or(Error::ArithmeticError)?;
 
@@ -294,18 +298,17 @@ impl Margins {
         trader: &Address,
         rwa_token: &Address,
     ) -> Result<i128, Error> {
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
-        let market = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
+        let market = Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
 
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        let current_price = Oracles::get_price(env, rwa_token)?;
 
         let unrealized_pnl = Liquidations::calculate_unrealized_pnl(&position, current_price)?;
         let position_value = Liquidations::calculate_position_value(&position, current_price)?;
-        let effective_margin = position.margin
+        let effective_margin = position
+            .margin
             .checked_add(unrealized_pnl)
             .ok_or(Error::ArithmeticError)?;
 
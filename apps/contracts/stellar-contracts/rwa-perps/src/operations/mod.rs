@@ -1,4 +1,7 @@
 pub mod funding;
+pub mod insurance;
 pub mod liquidation;
 pub mod margin;
+pub mod oracles;
 pub mod positions;
+pub mod triggers;
@@ -0,0 +1,131 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+use crate::common::error::Error;
+use crate::common::events::Events;
+use crate::common::storage::Storage;
+use crate::common::types::SCALAR_9;
+use crate::operations::funding::Funding;
+use crate::rwa_oracle::{self, Asset};
+
+/// Oracle integration for fetching market prices
+pub struct Oracles;
+
+impl Oracles {
+    /// Get the current price for a market (9 decimals)
+    ///
+    /// Reads the RWA Oracle's `lastprice` for the symbol the market is bound to
+    /// via `set_market_oracle_asset`. Markets without a binding have no price.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `rwa_token` - Address of the RWA token market
+    ///
+    /// # Returns
+    /// * `Ok(price)` - Current price scaled to SCALAR_9
+    /// * `Err(Error::OracleAssetNotSet)` - Market has no oracle binding
+    /// * `Err(Error::OraclePriceNotFound)` - Oracle has no valid price for the bound asset
+    pub fn get_price(env: &Env, rwa_token: &Address) -> Result<i128, Error> {
+        let asset_id =
+            Storage::get_market_oracle_asset(env, rwa_token).ok_or(Error::OracleAssetNotSet)?;
+
+        Self::get_oracle_price(env, &asset_id)
+    }
+
+    /// Get the latest price for an oracle asset symbol, normalized to 9 decimals
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `asset_id` - Oracle asset symbol
+    ///
+    /// # Returns
+    /// * `Ok(price)` - Latest oracle price scaled to SCALAR_9
+    /// * `Err(Error::OraclePriceNotFound)` - No price, or a non-positive price
+    pub fn get_oracle_price(env: &Env, asset_id: &Symbol) -> Result<i128, Error> {
+        let oracle_client = rwa_oracle::Client::new(env, &Storage::get_oracle(env));
+
+        let price_data = oracle_client
+            .lastprice(&Asset::Other(asset_id.clone()))
+            .ok_or(Error::OraclePriceNotFound)?;
+
+        if price_data.price <= 0 {
+            return Err(Error::OraclePriceNotFound);
+        }
+
+        // Normalize from oracle decimals to SCALAR_9
+        let oracle_scalar = 10i128
+            .checked_pow(oracle_client.decimals())
+            .ok_or(Error::ArithmeticError)?;
+
+        price_data
+            .price
+            .checked_mul(SCALAR_9)
+            .ok_or(Error::ArithmeticError)?
+            .checked_div(oracle_scalar)
+            .ok_or(Error::DivisionByZero)
+    }
+
+    /// Get the age in seconds of a market's bound oracle price
+    ///
+    /// # Returns
+    /// * `Some(age)` - Seconds since the oracle's latest price for the bound asset
+    /// * `None` - Market has no oracle binding or the oracle has no price
+    pub fn get_price_age(env: &Env, rwa_token: &Address) -> Option<u64> {
+        let asset_id = Storage::get_market_oracle_asset(env, rwa_token)?;
+        let oracle_client = rwa_oracle::Client::new(env, &Storage::get_oracle(env));
+        let price_data = oracle_client.lastprice(&Asset::Other(asset_id))?;
+
+        Some(
+            env.ledger()
+                .timestamp()
+                .saturating_sub(price_data.timestamp),
+        )
+    }
+
+    /// Check whether a market's oracle price is within its configured max age
+    ///
+    /// Markets without a max age, without an oracle binding, or without an
+    /// oracle price are treated as fresh.
+    pub fn is_price_fresh(env: &Env, rwa_token: &Address) -> bool {
+        let max_age = Storage::get_max_price_age(env, rwa_token).unwrap_or(0);
+        if max_age == 0 {
+            return true;
+        }
+
+        match Self::get_price_age(env, rwa_token) {
+            Some(age) => age <= max_age,
+            None => true,
+        }
+    }
+
+    /// Deactivate a market whose oracle price is older than its max age
+    ///
+    /// Callable by anyone (e.g. a keeper). A failed `open_position` rolls back
+    /// its own writes, so this is where the pause is persisted. The market stays
+    /// inactive until the admin re-enables it via `set_market_config`.
+    ///
+    /// # Returns
+    /// * `Ok(true)` - Market is active and its price is fresh
+    /// * `Ok(false)` - Market is inactive (possibly deactivated by this call)
+    /// * `Err(Error::MarketNotFound)` - Market does not exist
+    pub fn check_market_health(env: &Env, rwa_token: &Address) -> Result<bool, Error> {
+        let mut market = Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
+
+        if !market.is_active {
+            return Ok(false);
+        }
+        if Self::is_price_fresh(env, rwa_token) {
+            return Ok(true);
+        }
+
+        // Close out funding for the active span before pausing
+        Funding::checkpoint_funding_index(env, rwa_token, &market)?;
+        market.is_active = false;
+        Storage::set_market_config(env, rwa_token, &market);
+
+        let max_age = Storage::get_max_price_age(env, rwa_token).unwrap_or(0);
+        let price_age = Self::get_price_age(env, rwa_token).unwrap_or(0);
+        Events::market_auto_paused(env, rwa_token, price_age, max_age);
+
+        Ok(false)
+    }
+}
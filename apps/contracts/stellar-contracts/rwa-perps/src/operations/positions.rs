@@ -1,11 +1,15 @@
-use soroban_sdk::{Address, Env, Vec};
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
 use soroban_sdk::token::TokenClient;
+use soroban_sdk::{Address, Env, IntoVal, Symbol, Vec, symbol_short, vec};
 
 use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::common::storage::Storage;
-use crate::common::types::{Position, BASIS_POINTS, SCALAR_9};
+use crate::common::types::{BASIS_POINTS, MarketConfig, Position, SCALAR_9};
+use crate::operations::funding::Funding;
+use crate::operations::insurance::Insurance;
 use crate::operations::liquidation::Liquidations;
+use crate::operations::oracles::Oracles;
 
 /// Position management functions for RWA Perpetuals
 pub struct Positions;
@@ -41,10 +45,14 @@ impl Positions {
     /// * `MarketNotFound` - Market configuration not found
     /// * `MarketInactive` - Market is not active
     /// * `ExceedsMaxLeverage` - Leverage exceeds market maximum
-    /// * `InsufficientInitialMargin` - Margin below initial requirement
+    /// * `InsufficientInitialMargin` - Margin below initial requirement or the market's absolute floor
     /// * `PositionAlreadyExists` - Trader already has a position for this token
+    /// * `OpenCooldownActive` - Trader closed on this market within the open cooldown
+    /// * `TraderNotVerified` - Market is KYC-gated and the trader is not verified
     /// * `MarginTokenNotSet` - Margin token not configured
+    /// * `OracleAssetNotSet` - Market has no oracle asset binding
     /// * `OraclePriceNotFound` - Cannot fetch current price from oracle
+    /// * `OraclePriceStale` - Oracle price is older than the market's max price age
     /// * `ArithmeticError` - Overflow in calculations
     /// * `DivisionByZero` - Division by zero in calculations
     pub fn open_position(
@@ -76,9 +84,8 @@ impl Positions {
         }
 
         // 4. Get and validate market config
-        let market = Storage::get_market_config(env, rwa_token)
-            .ok_or(Error::MarketNotFound)?;
-        
+        let market = Storage::get_market_config(env, rwa_token).ok_or(Error::MarketNotFound)?;
+
         if !market.is_active {
             return Err(Error::MarketInactive);
         }
@@ -87,17 +94,16 @@ impl Positions {
             return Err(Error::ExceedsMaxLeverage);
         }
 
-        // 5. Get current price from oracle
-        // TODO: Integrate with actual RWA oracle contract using SEP-40 interface
-        // For now, use storage-based price (same pattern as margin.rs)
-        // Production implementation should use:
-        // let oracle_client = RWAOracleClient::new(env, &storage.oracle);
-        // let asset_symbol = oracle_client.get_asset_id_from_token(rwa_token)?;
-        // let asset = Asset::Other(asset_symbol);
-        // let price_data = oracle_client.lastprice(&asset)?;
-        // let current_price = price_data.price;
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        Self::require_verified_trader(env, trader, rwa_token)?;
+
+        // Refuse to open against a frozen feed; keepers persist the pause
+        // through `check_market_health`
+        if !Oracles::is_price_fresh(env, rwa_token) {
+            return Err(Error::OraclePriceStale);
+        }
+
+        // 5. Get current price from the market's bound oracle asset
+        let current_price = Oracles::get_price(env, rwa_token)?;
 
         // 6. Calculate position value
         let abs_size = if size < 0 {
@@ -105,7 +111,7 @@ impl Positions {
         } else {
             size
         };
-        
+
         let position_value = abs_size
             .checked_mul(current_price)
             .ok_or(Error::ArithmeticError)?
@@ -114,7 +120,7 @@ impl Positions {
 
         // 7. Validate margin requirements
         let required_initial_margin = position_value
-            .checked_mul(market.initial_margin as i128)
+            .checked_mul(Self::initial_margin_for_leverage(&market, leverage) as i128)
             .ok_or(Error::ArithmeticError)?
             .checked_div(BASIS_POINTS)
             .ok_or(Error::DivisionByZero)?;
@@ -123,19 +129,34 @@ impl Positions {
             return Err(Error::InsufficientInitialMargin);
         }
 
+        let min_absolute_margin = Storage::get_min_absolute_margin(env, rwa_token).unwrap_or(0);
+        if margin < min_absolute_margin {
+            return Err(Error::InsufficientInitialMargin);
+        }
+
         // 8. Check for existing position
         if Storage::get_position(env, trader, rwa_token).is_some() {
             return Err(Error::PositionAlreadyExists);
         }
 
+        // Enforce the cooldown since the trader's last close on this market
+        if storage.open_cooldown_seconds > 0
+            && let Some(closed_at) = Storage::get_last_close(env, trader, rwa_token)
+        {
+            let elapsed = env.ledger().timestamp().saturating_sub(closed_at);
+            if elapsed < storage.open_cooldown_seconds {
+                return Err(Error::OpenCooldownActive);
+            }
+        }
+
         // 9. Transfer margin from trader to contract
-        let margin_token = Storage::get_margin_token(env)
-            .ok_or(Error::MarginTokenNotSet)?;
+        let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
         let token_client = TokenClient::new(env, &margin_token);
         let contract_address = env.current_contract_address();
         token_client.transfer(trader, &contract_address, &margin);
 
         // 10. Create Position struct and store
+        let entry_funding_index = Funding::current_funding_index(env, rwa_token, &market)?;
         let position = Position {
             trader: trader.clone(),
             rwa_token: rwa_token.clone(),
@@ -145,15 +166,29 @@ impl Positions {
             leverage,
             opened_at: env.ledger().timestamp(),
             last_funding_payment: 0,
+            entry_funding_index,
+            funding_carry: 0,
         };
-        
+
         Storage::set_position(env, trader, rwa_token, &position);
 
-        // 11. Add rwa_token to trader's token list
+        // 11. Add rwa_token to trader's token list and track open notional
         Storage::add_trader_token(env, trader, rwa_token);
+        let notional = Insurance::notional(abs_size, current_price)?;
+        Insurance::adjust_open_notional(env, notional)?;
 
         // 12. Emit position_opened event
-        Events::position_opened(env, trader, rwa_token, size, current_price, margin, leverage);
+        Events::position_opened(
+            env,
+            trader,
+            rwa_token,
+            size,
+            current_price,
+            margin,
+            leverage,
+            notional,
+            &margin_token,
+        );
 
         Ok(())
     }
@@ -161,24 +196,29 @@ impl Positions {
     /// Close a position (full or partial)
     ///
     /// Closes all or part of an existing position, calculating P&L based on current market price
-    /// and transferring the appropriate payout (margin + P&L) back to the trader.
+    /// and transferring the appropriate payout (margin + P&L) back to the trader. Owed and
+    /// carried funding is realized into margin first, without the per-settlement cap.
     ///
     /// # Arguments
     /// * `env` - The contract environment
     /// * `trader` - Address of the position owner (must authorize transaction)
     /// * `rwa_token` - Address of the RWA token for the position
     /// * `size_to_close` - Absolute size to close (must be > 0 and <= abs(position.size))
+    /// * `min_payout_out` - Minimum acceptable payout (0 disables the guard)
     ///
     /// # Returns
     /// * `Ok(())` - Position successfully closed (full or partial)
     /// * `Err(Error)` - Various errors (see error codes below)
     ///
     /// # Errors
-    /// * `InvalidInput` - size_to_close is <= 0 or exceeds position size
+    /// * `InvalidInput` - size_to_close is <= 0, exceeds position size, or min_payout_out < 0
+    /// * `SlippageExceeded` - Computed payout is below min_payout_out
     /// * `ProtocolPaused` - Protocol operations are paused
     /// * `PositionNotFound` - Position doesn't exist
+    /// * `OracleAssetNotSet` - Market has no oracle asset binding
     /// * `OraclePriceNotFound` - Cannot fetch current price from oracle
     /// * `MarginTokenNotSet` - Margin token not configured
+    /// * `InsufficientProtocolLiquidity` - Contract holds less margin token than the payout
     /// * `ArithmeticError` - Overflow in calculations
     /// * `DivisionByZero` - Division by zero in calculations
     pub fn close_position(
@@ -186,10 +226,108 @@ impl Positions {
         trader: &Address,
         rwa_token: &Address,
         size_to_close: i128,
+        min_payout_out: i128,
     ) -> Result<(), Error> {
         // 1. Authorization
         trader.require_auth();
 
+        Self::settle_close(
+            env,
+            trader,
+            rwa_token,
+            size_to_close,
+            min_payout_out,
+            trader,
+        )?;
+        Ok(())
+    }
+
+    /// Close a position and post the payout as collateral in a lending pool
+    ///
+    /// Closes like `close_position` (without a minimum payout), keeping the
+    /// payout in this contract, and then calls
+    /// `deposit_collateral_for(this, trader, margin_token, payout)` on
+    /// `lending_contract`, which pulls the payout straight from this contract
+    /// into the trader's CDP. Proceeds never pass through the trader's wallet.
+    /// The lending pool must trust this contract as a collateral depositor and
+    /// accept the margin token as collateral.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `trader` - Address of the position owner (must authorize transaction)
+    /// * `rwa_token` - Address of the RWA token for the position
+    /// * `size_to_close` - Absolute size to close (must be > 0 and <= abs(position.size))
+    /// * `lending_contract` - Lending pool receiving the payout as collateral
+    ///
+    /// # Returns
+    /// * `Ok(payout)` - Amount of margin token posted as collateral
+    /// * `Err(Error)` - Any error returned by `close_position`
+    pub fn close_to_collateral(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        size_to_close: i128,
+        lending_contract: &Address,
+    ) -> Result<i128, Error> {
+        trader.require_auth();
+
+        let contract_address = env.current_contract_address();
+        let payout =
+            Self::settle_close(env, trader, rwa_token, size_to_close, 0, &contract_address)?;
+
+        if payout > 0 {
+            let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
+
+            // Let the lending pool pull the payout from this contract
+            env.authorize_as_current_contract(vec![
+                env,
+                InvokerContractAuthEntry::Contract(SubContractInvocation {
+                    context: ContractContext {
+                        contract: margin_token.clone(),
+                        fn_name: symbol_short!("transfer"),
+                        args: vec![
+                            env,
+                            contract_address.clone().into_val(env),
+                            lending_contract.clone().into_val(env),
+                            payout.into_val(env),
+                        ],
+                    },
+                    sub_invocations: Vec::new(env),
+                }),
+            ]);
+            env.invoke_contract::<()>(
+                lending_contract,
+                &Symbol::new(env, "deposit_collateral_for"),
+                vec![
+                    env,
+                    contract_address.into_val(env),
+                    trader.clone().into_val(env),
+                    margin_token.into_val(env),
+                    payout.into_val(env),
+                ],
+            );
+
+            Events::payout_to_collateral(env, trader, rwa_token, lending_contract, payout);
+        }
+
+        Ok(payout)
+    }
+
+    /// Close a position without requiring the trader's authorization
+    ///
+    /// Shared by `close_position`, `close_to_collateral` and keeper-executed
+    /// trigger orders; callers are responsible for authorizing the close.
+    /// Errors match `close_position`. The payout is transferred to `recipient`,
+    /// or kept by this contract when `recipient` is the contract itself, and
+    /// is returned.
+    pub(crate) fn settle_close(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        size_to_close: i128,
+        min_payout_out: i128,
+        recipient: &Address,
+    ) -> Result<i128, Error> {
         // 2. Protocol state validation
         let storage = Storage::get(env);
         if storage.protocol_paused {
@@ -200,10 +338,13 @@ impl Positions {
         if size_to_close <= 0 {
             return Err(Error::InvalidInput);
         }
+        if min_payout_out < 0 {
+            return Err(Error::InvalidInput);
+        }
 
         // 4. Get position
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let mut position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
         // 5. Validate size_to_close
         let abs_position_size = if position.size < 0 {
@@ -216,62 +357,77 @@ impl Positions {
             return Err(Error::InvalidInput);
         }
 
-        // 6. Get current price from oracle
-        // TODO: Migration to SEP-40 Oracle Client. 
-        // Current implementation uses storage-cached prices to match margin.rs pattern.
-        // Integration should target the `lastprice` method from the RWA Oracle contract.
-        let current_price = Storage::get_current_price(env, rwa_token)
-            .ok_or(Error::OraclePriceNotFound)?;
+        // 6. Get current price from the market's bound oracle asset
+        let current_price = Oracles::get_price(env, rwa_token)?;
+
+        // Settle funding into margin under the per-settlement cap; any carry left
+        // over is still owed and comes out of a full close's payout below
+        Funding::settle_position_funding(env, rwa_token, &mut position)?;
 
         // 7. Calculate P&L and payout
         let total_pnl = Liquidations::calculate_unrealized_pnl(&position, current_price)?;
-        
+
         // Determine if this is a full or partial close
         let is_full_close = size_to_close == abs_position_size;
-        
+
         let (pnl_for_close, margin_to_return, payout) = if is_full_close {
-            // Full close: return all remaining margin + total P&L
+            // Full close: return all remaining margin + total P&L, less carried funding
             // This avoids dust from rounding errors
-            let payout_amount = position.margin
+            let payout_amount = position
+                .margin
                 .checked_add(total_pnl)
+                .and_then(|value| value.checked_sub(position.funding_carry))
                 .ok_or(Error::ArithmeticError)?
                 .max(0); // Prevent negative payouts
-            
+
             (total_pnl, position.margin, payout_amount)
         } else {
             // Partial close: prorate margin and P&L
             // IMPORTANT: Multiply first, then divide to preserve precision
-            
+
             // Prorate P&L: pnl_for_close = (total_pnl * size_to_close) / abs(position.size)
             let pnl_partial = total_pnl
                 .checked_mul(size_to_close)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(abs_position_size)
                 .ok_or(Error::DivisionByZero)?;
-            
+
             // Prorate margin: margin_to_return = (position.margin * size_to_close) / abs(position.size)
-            let margin_partial = position.margin
+            let margin_partial = position
+                .margin
                 .checked_mul(size_to_close)
                 .ok_or(Error::ArithmeticError)?
                 .checked_div(abs_position_size)
                 .ok_or(Error::DivisionByZero)?;
-            
+
             // Calculate payout: margin + P&L (capped at 0 if negative)
             let payout_amount = margin_partial
                 .checked_add(pnl_partial)
                 .ok_or(Error::ArithmeticError)?
                 .max(0); // Prevent negative payouts
-            
+
             (pnl_partial, margin_partial, payout_amount)
         };
 
-        // 8. Transfer payout to trader (only if > 0)
+        // Slippage protection: payout must meet the trader's minimum
+        if min_payout_out > 0 && payout < min_payout_out {
+            return Err(Error::SlippageExceeded);
+        }
+
+        // 8. Transfer payout to the recipient (only if > 0)
         if payout > 0 {
-            let margin_token = Storage::get_margin_token(env)
-                .ok_or(Error::MarginTokenNotSet)?;
+            let margin_token = Storage::get_margin_token(env).ok_or(Error::MarginTokenNotSet)?;
             let token_client = TokenClient::new(env, &margin_token);
             let contract_address = env.current_contract_address();
-            token_client.transfer(&contract_address, trader, &payout);
+
+            // Fail clearly if accounting has drifted past what the contract holds
+            if token_client.balance(&contract_address) < payout {
+                return Err(Error::InsufficientProtocolLiquidity);
+            }
+
+            if recipient != &contract_address {
+                token_client.transfer(&contract_address, recipient, &payout);
+            }
         }
 
         // 9. Update or remove position
@@ -279,10 +435,13 @@ impl Positions {
             // Full close: remove position
             Storage::remove_position(env, trader, rwa_token);
             Storage::remove_trader_token(env, trader, rwa_token);
+            Storage::remove_trigger_order(env, trader, rwa_token);
+            Storage::set_last_close(env, trader, rwa_token, env.ledger().timestamp());
             0
         } else {
             // Partial close: update position
-            let remaining_margin = position.margin
+            let remaining_margin = position
+                .margin
                 .checked_sub(margin_to_return)
                 .ok_or(Error::ArithmeticError)?;
 
@@ -293,7 +452,9 @@ impl Positions {
 
             // Apply sign based on original position direction (long/short)
             let new_size = if position.size < 0 {
-                remaining_abs_size.checked_neg().ok_or(Error::ArithmeticError)?
+                remaining_abs_size
+                    .checked_neg()
+                    .ok_or(Error::ArithmeticError)?
             } else {
                 remaining_abs_size
             };
@@ -308,7 +469,16 @@ impl Positions {
             new_size
         };
 
-        // 10. Emit position_closed event
+        // 10. Release the closed portion of open notional
+        let closed_notional = Insurance::notional(abs_position_size, position.entry_price)?
+            .checked_sub(Insurance::notional(
+                remaining_size.abs(),
+                position.entry_price,
+            )?)
+            .ok_or(Error::ArithmeticError)?;
+        Insurance::adjust_open_notional(env, -closed_notional)?;
+
+        // 11. Emit position_closed event
         Events::position_closed(
             env,
             trader,
@@ -317,7 +487,108 @@ impl Positions {
             current_price,
             pnl_for_close,
             remaining_size,
+            closed_notional,
+            position.leverage,
+            &Storage::get_margin_token(env),
+        );
+
+        Ok(payout)
+    }
+
+    /// Move an open position to a new owner
+    ///
+    /// Both parties must authorize: `from` gives up the position and `to`
+    /// accepts it. The position keeps its size, entry price, margin and funding
+    /// state; only the owner changes. Both traders' token indexes are updated
+    /// and any trigger order set by `from` is dropped.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - Current owner of the position
+    /// * `to` - New owner of the position
+    /// * `rwa_token` - Address of the RWA token market
+    ///
+    /// # Errors
+    /// * `InvalidInput` - `from` and `to` are the same address
+    /// * `ProtocolPaused` - Protocol operations are paused
+    /// * `PositionNotFound` - `from` has no position on this market
+    /// * `PositionAlreadyExists` - `to` already has a position on this market
+    /// * `TraderNotVerified` - Market is KYC-gated and `to` is not verified
+    pub fn transfer_position(
+        env: &Env,
+        from: &Address,
+        to: &Address,
+        rwa_token: &Address,
+    ) -> Result<(), Error> {
+        from.require_auth();
+        to.require_auth();
+
+        if from == to {
+            return Err(Error::InvalidInput);
+        }
+
+        if Storage::get(env).protocol_paused {
+            return Err(Error::ProtocolPaused);
+        }
+
+        let mut position =
+            Storage::get_position(env, from, rwa_token).ok_or(Error::PositionNotFound)?;
+
+        if Storage::get_position(env, to, rwa_token).is_some() {
+            return Err(Error::PositionAlreadyExists);
+        }
+
+        Self::require_verified_trader(env, to, rwa_token)?;
+
+        position.trader = to.clone();
+        Storage::set_position(env, to, rwa_token, &position);
+        Storage::add_trader_token(env, to, rwa_token);
+
+        Storage::remove_position(env, from, rwa_token);
+        Storage::remove_trader_token(env, from, rwa_token);
+        Storage::remove_trigger_order(env, from, rwa_token);
+
+        Events::position_transferred(env, from, to, rwa_token, position.size, position.margin);
+
+        Ok(())
+    }
+
+    /// Initial margin (basis points) required at a given leverage
+    ///
+    /// Uses the highest tier in `initial_margin_tiers` whose minimum leverage
+    /// is at or below `leverage`, falling back to the flat `initial_margin`.
+    pub fn initial_margin_for_leverage(market: &MarketConfig, leverage: u32) -> u32 {
+        let mut initial_margin = market.initial_margin;
+        for (min_leverage, tier_margin) in market.initial_margin_tiers.iter() {
+            if min_leverage > leverage {
+                break;
+            }
+            initial_margin = tier_margin;
+        }
+        initial_margin
+    }
+
+    /// Require the trader to be verified when the market is KYC-gated
+    ///
+    /// Gated markets without a configured identity verifier reject every trader.
+    fn require_verified_trader(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+    ) -> Result<(), Error> {
+        if !Storage::get_requires_kyc(env, rwa_token).unwrap_or(false) {
+            return Ok(());
+        }
+
+        let verifier = Storage::get_identity_verifier(env).ok_or(Error::TraderNotVerified)?;
+        let verified: bool = env.invoke_contract(
+            &verifier,
+            &Symbol::new(env, "is_verified"),
+            vec![env, trader.clone().into_val(env)],
         );
+        if !verified {
+            return Err(Error::TraderNotVerified);
+        }
 
         Ok(())
     }
@@ -339,8 +610,8 @@ impl Positions {
         trader: &Address,
         rwa_token: &Address,
     ) -> Result<Position, Error> {
-        let position = Storage::get_position(env, trader, rwa_token)
-            .ok_or(Error::PositionNotFound)?;
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
 
         // Emit position_queried event
         Events::position_queried(env, trader, rwa_token, position.size, position.margin);
@@ -358,10 +629,7 @@ impl Positions {
     ///
     /// # Returns
     /// * `Vec<Position>` - Vector of all positions (empty if trader has no positions)
-    pub fn get_user_positions(
-        env: &Env,
-        trader: &Address,
-    ) -> Vec<Position> {
+    pub fn get_user_positions(env: &Env, trader: &Address) -> Vec<Position> {
         let mut positions = Vec::new(env);
 
         // Get all rwa_tokens for trader
@@ -0,0 +1,152 @@
+use soroban_sdk::{Address, Env};
+
+use crate::common::error::Error;
+use crate::common::events::Events;
+use crate::common::storage::Storage;
+use crate::common::types::{Position, TriggerOrder};
+use crate::operations::oracles::Oracles;
+use crate::operations::positions::Positions;
+
+/// Stop-loss / take-profit trigger orders for RWA Perpetuals
+pub struct Triggers;
+
+impl Triggers {
+    /// Attach a stop-loss and/or take-profit to an open position
+    ///
+    /// Replaces any existing trigger order on the position. A price of 0
+    /// leaves that side unset.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `trader` - Address of the position owner (must authorize transaction)
+    /// * `rwa_token` - Address of the RWA token for the position
+    /// * `stop_loss_price` - Close once the price moves against the position to this level
+    /// * `take_profit_price` - Close once the price moves in favour of the position to this level
+    ///
+    /// # Errors
+    /// * `InvalidInput` - A price is negative or both prices are 0
+    /// * `PositionNotFound` - Position doesn't exist
+    pub fn set_trigger_order(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+        stop_loss_price: i128,
+        take_profit_price: i128,
+    ) -> Result<(), Error> {
+        trader.require_auth();
+
+        if stop_loss_price < 0 || take_profit_price < 0 {
+            return Err(Error::InvalidInput);
+        }
+        if stop_loss_price == 0 && take_profit_price == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        if Storage::get_position(env, trader, rwa_token).is_none() {
+            return Err(Error::PositionNotFound);
+        }
+
+        let order = TriggerOrder {
+            stop_loss_price,
+            take_profit_price,
+        };
+        Storage::set_trigger_order(env, trader, rwa_token, &order);
+
+        Events::trigger_order_set(env, trader, rwa_token, stop_loss_price, take_profit_price);
+
+        Ok(())
+    }
+
+    /// Remove the trigger order from a position
+    pub fn cancel_trigger_order(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+    ) -> Result<(), Error> {
+        trader.require_auth();
+
+        if Storage::get_trigger_order(env, trader, rwa_token).is_none() {
+            return Err(Error::InvalidInput);
+        }
+
+        Storage::remove_trigger_order(env, trader, rwa_token);
+        Ok(())
+    }
+
+    /// Fully close a position whose stop-loss or take-profit has been reached
+    ///
+    /// Callable by any keeper. Triggers only go live once the position has been
+    /// open for the market's `trigger_activation_delay`, so a wick right after
+    /// opening cannot fire a pre-set stop.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `keeper` - Address executing the trigger (must authorize transaction)
+    /// * `trader` - Address of the position owner
+    /// * `rwa_token` - Address of the RWA token for the position
+    ///
+    /// # Errors
+    /// * `PositionNotFound` - Position doesn't exist
+    /// * `InvalidInput` - Position has no trigger order
+    /// * `TriggerNotActive` - Position is younger than the trigger activation delay
+    /// * `TriggerNotMet` - Price has not reached the stop-loss or take-profit
+    /// * Any error returned by `close_position`
+    pub fn execute_trigger_order(
+        env: &Env,
+        keeper: &Address,
+        trader: &Address,
+        rwa_token: &Address,
+    ) -> Result<(), Error> {
+        keeper.require_auth();
+
+        let position =
+            Storage::get_position(env, trader, rwa_token).ok_or(Error::PositionNotFound)?;
+        let order =
+            Storage::get_trigger_order(env, trader, rwa_token).ok_or(Error::InvalidInput)?;
+
+        let delay = Storage::get_trigger_activation_delay(env, rwa_token).unwrap_or(0);
+        if env.ledger().timestamp() < position.opened_at.saturating_add(delay) {
+            return Err(Error::TriggerNotActive);
+        }
+
+        let current_price = Oracles::get_price(env, rwa_token)?;
+        if !Self::is_triggered(&position, &order, current_price) {
+            return Err(Error::TriggerNotMet);
+        }
+
+        Positions::settle_close(env, trader, rwa_token, position.size.abs(), 0, trader)?;
+
+        Events::trigger_executed(env, keeper, trader, rwa_token, current_price);
+
+        Ok(())
+    }
+
+    /// Get the trigger order attached to a position
+    pub fn get_trigger_order(
+        env: &Env,
+        trader: &Address,
+        rwa_token: &Address,
+    ) -> Option<TriggerOrder> {
+        Storage::get_trigger_order(env, trader, rwa_token)
+    }
+
+    /// Whether the current price has reached either side of the trigger order
+    fn is_triggered(position: &Position, order: &TriggerOrder, price: i128) -> bool {
+        let is_long = position.size > 0;
+
+        let stop_hit = order.stop_loss_price > 0
+            && if is_long {
+                price <= order.stop_loss_price
+            } else {
+                price >= order.stop_loss_price
+            };
+        let take_profit_hit = order.take_profit_price > 0
+            && if is_long {
+                price >= order.take_profit_price
+            } else {
+                price <= order.take_profit_price
+            };
+
+        stop_hit || take_profit_hit
+    }
+}
@@ -42,4 +42,13 @@ pub enum Error {
 
     /// Contract is already initialized
     AlreadyInitialized = 13,
+
+    /// Token is not a fund valued by NAV, so it cannot be redeemed
+    NotNavValued = 14,
+
+    /// Redemption reserve cannot cover the NAV payout
+    InsufficientRedemptionReserve = 15,
+
+    /// No redemption settlement asset configured
+    RedemptionNotConfigured = 16,
 }
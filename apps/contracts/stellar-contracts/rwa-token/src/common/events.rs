@@ -45,6 +45,15 @@ pub struct ClawbackEvent {
     pub amount: i128,
 }
 
+/// Redeem event emitted when tokens are redeemed at NAV
+#[contractevent]
+pub struct RedeemEvent {
+    #[topic]
+    pub holder: Address,
+    pub amount: i128,
+    pub payout: i128,
+}
+
 /// Event emission utilities
 pub struct Events;
 
@@ -97,4 +106,13 @@ impl Events {
         }
         .publish(env);
     }
+
+    pub fn redeem(env: &Env, holder: &Address, amount: i128, payout: i128) {
+        RedeemEvent {
+            holder: holder.clone(),
+            amount,
+            payout,
+        }
+        .publish(env);
+    }
 }
@@ -17,6 +17,7 @@ pub const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
 pub const COMPLIANCE_KEY: Symbol = symbol_short!("COMPL");
 pub const IDENTITY_KEY: Symbol = symbol_short!("IDENT");
 pub const TOTAL_SUPPLY_KEY: Symbol = symbol_short!("SUPPLY");
+pub const REDEMPTION_ASSET_KEY: Symbol = symbol_short!("REDEEM");
 
 /// Token metadata storage (instance storage)
 #[contracttype]
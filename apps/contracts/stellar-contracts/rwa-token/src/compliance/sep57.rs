@@ -39,7 +39,19 @@ impl Compliance {
         AuthorizationStorage::require_authorized(env, from);
         AuthorizationStorage::require_authorized(env, to);
 
-        // Delegate to SEP-57 compliance contract if configured
+        Self::check_compliance_contract(env, from, to, amount);
+    }
+
+    /// Check compliance requirements before a NAV redemption.
+    /// The holder must not be frozen; the compliance contract (if configured)
+    /// sees the redemption as a transfer from the holder to this contract.
+    pub fn check_redemption(env: &Env, holder: &Address, amount: i128) {
+        AuthorizationStorage::require_authorized(env, holder);
+        Self::check_compliance_contract(env, holder, &env.current_contract_address(), amount);
+    }
+
+    /// Delegate to SEP-57 compliance contract if configured
+    fn check_compliance_contract(env: &Env, from: &Address, to: &Address, amount: i128) {
         if let Some(compliance_addr) = Self::get_compliance(env) {
             let can_transfer: bool = env.invoke_contract(
                 &compliance_addr,
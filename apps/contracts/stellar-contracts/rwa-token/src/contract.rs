@@ -8,6 +8,7 @@ use crate::common::error::Error;
 use crate::common::events::Events;
 use crate::compliance::sep57::Compliance;
 use crate::oracle::Oracle;
+use crate::redemption::Redemption;
 use crate::token::allowance::AllowanceStorage;
 use crate::token::interface::{TokenInterface, TokenInterfaceImpl};
 
@@ -158,6 +159,30 @@ impl RWATokenContract {
     pub fn get_asset_type(env: Env) -> Result<crate::rwa_oracle::RWAAssetType, Error> {
         Oracle::get_asset_type(&env)
     }
+
+    // ==================== NAV Redemption ====================
+
+    /// Set the settlement asset paid out on NAV redemptions. Admin-only.
+    pub fn set_redemption_asset(env: Env, asset: Address) {
+        Admin::require_admin(&env);
+        Redemption::set_redemption_asset(&env, &asset);
+    }
+
+    /// Get the settlement asset paid out on NAV redemptions (if configured)
+    pub fn redemption_asset(env: Env) -> Option<Address> {
+        Redemption::get_redemption_asset(&env)
+    }
+
+    /// Get the settlement asset balance available for redemptions
+    pub fn redemption_reserve(env: Env) -> i128 {
+        Redemption::get_reserve(&env)
+    }
+
+    /// Redeem tokens at the oracle NAV for the settlement asset.
+    /// Only available for Fund tokens valued by NAV. Returns the payout.
+    pub fn redeem_at_nav(env: Env, holder: Address, amount: i128) -> i128 {
+        Redemption::redeem_at_nav(&env, &holder, amount)
+    }
 }
 
 // ==================== SEP-41 Token Interface ====================
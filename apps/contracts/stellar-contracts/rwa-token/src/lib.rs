@@ -5,6 +5,7 @@ pub mod token;
 pub mod compliance;
 pub mod oracle;
 pub mod admin;
+pub mod redemption;
 
 pub use common::error::Error;
 
@@ -0,0 +1,103 @@
+use soroban_sdk::{assert_with_error, panic_with_error, token, Address, Env};
+
+use crate::admin::supply::TotalSupplyStorage;
+use crate::common::error::Error;
+use crate::common::events::Events;
+use crate::common::metadata::MetadataStorage;
+use crate::common::types::REDEMPTION_ASSET_KEY;
+use crate::compliance::sep57::Compliance;
+use crate::oracle::Oracle;
+use crate::rwa_oracle::{RWAAssetType, ValuationMethod};
+use crate::token::balance::BalanceStorage;
+
+/// NAV redemption for fund tokens
+pub struct Redemption;
+
+impl Redemption {
+    /// Get the settlement asset paid out on redemption (if configured)
+    pub fn get_redemption_asset(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&REDEMPTION_ASSET_KEY)
+    }
+
+    /// Set the settlement asset paid out on redemption
+    pub fn set_redemption_asset(env: &Env, asset: &Address) {
+        env.storage().instance().set(&REDEMPTION_ASSET_KEY, asset);
+    }
+
+    /// Settlement asset held by this contract and available for redemptions
+    pub fn get_reserve(env: &Env) -> i128 {
+        match Self::get_redemption_asset(env) {
+            Some(asset) => {
+                token::Client::new(env, &asset).balance(&env.current_contract_address())
+            }
+            None => 0,
+        }
+    }
+
+    /// Redeem tokens at the oracle NAV.
+    /// Burns `amount` from `holder` and pays out the NAV value in the
+    /// settlement asset from the contract's redemption reserve.
+    pub fn redeem_at_nav(env: &Env, holder: &Address, amount: i128) -> i128 {
+        holder.require_auth();
+        assert_with_error!(env, amount > 0, Error::ValueNotPositive);
+
+        let settlement_asset = Self::get_redemption_asset(env)
+            .unwrap_or_else(|| panic_with_error!(env, Error::RedemptionNotConfigured));
+
+        // Only fund tokens valued by NAV are redeemable
+        let metadata = Oracle::get_rwa_metadata(env).unwrap_or_else(|e| panic_with_error!(env, e));
+        if !matches!(metadata.asset_type, RWAAssetType::Fund)
+            || !matches!(metadata.valuation_method, ValuationMethod::Nav)
+        {
+            panic_with_error!(env, Error::NotNavValued);
+        }
+
+        Compliance::check_redemption(env, holder, amount);
+
+        let nav = Oracle::get_price(env).unwrap_or_else(|e| panic_with_error!(env, e));
+        let oracle_decimals = Oracle::get_decimals(env).unwrap_or_else(|e| panic_with_error!(env, e));
+        let settlement = token::Client::new(env, &settlement_asset);
+        let payout = Self::nav_value(
+            env,
+            amount,
+            nav.price,
+            oracle_decimals,
+            MetadataStorage::get_decimals(env),
+            settlement.decimals(),
+        );
+
+        let contract_address = env.current_contract_address();
+        if settlement.balance(&contract_address) < payout {
+            panic_with_error!(env, Error::InsufficientRedemptionReserve);
+        }
+
+        BalanceStorage::subtract(env, holder, amount);
+        TotalSupplyStorage::subtract(env, amount);
+        settlement.transfer(&contract_address, holder, &payout);
+
+        Events::redeem(env, holder, amount, payout);
+        payout
+    }
+
+    /// Convert a token amount to settlement asset units at the given NAV
+    fn nav_value(
+        env: &Env,
+        amount: i128,
+        price: i128,
+        oracle_decimals: u32,
+        token_decimals: u32,
+        settlement_decimals: u32,
+    ) -> i128 {
+        let value = amount
+            .checked_mul(price)
+            .and_then(|v| v.checked_div(10i128.pow(oracle_decimals)))
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticError));
+
+        let scaled = if settlement_decimals >= token_decimals {
+            value.checked_mul(10i128.pow(settlement_decimals - token_decimals))
+        } else {
+            value.checked_div(10i128.pow(token_decimals - settlement_decimals))
+        };
+        scaled.unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticError))
+    }
+}